@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use flate2::read::GzDecoder;
+use walkdir::WalkDir;
+use crate::path_rules::PathRules;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    Zip,
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+    Lz4,
+    SevenZip,
+    Rar,
+    Tar,
+    Deb,
+    Rpm,
+}
+
+impl Format {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Zip => "zip",
+            Format::Gzip => "gzip",
+            Format::Bzip2 => "bzip2",
+            Format::Xz => "xz",
+            Format::Zstd => "zstd",
+            Format::Lz4 => "lz4",
+            Format::SevenZip => "7z",
+            Format::Rar => "rar",
+            Format::Tar => "tar",
+            Format::Deb => "deb",
+            Format::Rpm => "rpm",
+        }
+    }
+}
+
+//signatures and the offset they are found at; tar's is in the header, past the first file name
+const MAGIC: [(usize, &[u8], Format); 12] = [
+    (0, b"!<arch>\ndebian-binary", Format::Deb),
+    (0, b"PK\x03\x04", Format::Zip),
+    //an empty zip is just its end of central directory record
+    (0, b"PK\x05\x06", Format::Zip),
+    (0, b"\x1f\x8b", Format::Gzip),
+    (0, b"BZh", Format::Bzip2),
+    (0, b"\xfd7zXZ\x00", Format::Xz),
+    (0, b"\x28\xb5\x2f\xfd", Format::Zstd),
+    (0, b"\x04\x22\x4d\x18", Format::Lz4),
+    (0, b"7z\xbc\xaf\x27\x1c", Format::SevenZip),
+    (0, b"Rar!\x1a\x07", Format::Rar),
+    (0, b"\xed\xab\xee\xdb", Format::Rpm),
+    (257, b"ustar", Format::Tar),
+];
+
+//used when the content can't be read or matches no signature
+const EXTENSIONS: [(&str, Format); 20] = [
+    ("zip", Format::Zip),
+    ("gz", Format::Gzip),
+    ("tgz", Format::Gzip),
+    ("bz2", Format::Bzip2),
+    ("tbz", Format::Bzip2),
+    ("tbz2", Format::Bzip2),
+    ("xz", Format::Xz),
+    ("txz", Format::Xz),
+    ("zst", Format::Zstd),
+    ("tzst", Format::Zstd),
+    ("lz4", Format::Lz4),
+    ("7z", Format::SevenZip),
+    ("rar", Format::Rar),
+    ("tar", Format::Tar),
+    ("deb", Format::Deb),
+    ("rpm", Format::Rpm),
+    ("jar", Format::Zip),
+    ("apk", Format::Zip),
+    ("whl", Format::Zip),
+    ("xpi", Format::Zip),
+];
+
+pub fn detect(path: &Path) -> Option<Format> {
+    let mut header = [0; 262];
+    let read = crate::open_without_atime(path).and_then(|mut file| {
+        let mut read = 0;
+        //short reads are allowed, so keep going until the header is full or the file ends
+        while read < header.len() {
+            match file.read(&mut header[read..])? {
+                0 => break,
+                n => read += n,
+            }
+        }
+        Ok(read)
+    });
+    if let Ok(read) = read {
+        let header = &header[..read];
+        let found = MAGIC
+            .iter()
+            .find(|(offset, magic, _)| header.len() >= offset + magic.len() && &header[*offset..offset + magic.len()] == *magic)
+            .map(|(_, _, format)| *format);
+        if found.is_some() {
+            return found;
+        }
+    }
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    EXTENSIONS.iter().find(|(known, _)| *known == extension).map(|(_, format)| *format)
+}
+
+//number of archives and their size per format below root, biggest total first
+pub fn totals(root: &Path, excludes: &PathRules) -> Vec<(Format, usize, u64)> {
+    let mut totals: HashMap<Format, (usize, u64)> = HashMap::new();
+    let walker = WalkDir::new(root).into_iter().filter_entry(|entry| entry.depth() == 0 || !excludes.is_excluded(entry.path()));
+    for entry in walker.filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        if let Some(format) = detect(entry.path()) {
+            let total = totals.entry(format).or_default();
+            total.0 += 1;
+            total.1 += entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        }
+    }
+    let mut totals: Vec<(Format, usize, u64)> = totals.into_iter().map(|(format, (count, size))| (format, count, size)).collect();
+    totals.sort_by_key(|(_, _, size)| std::cmp::Reverse(*size));
+    totals
+}
+
+pub fn show(ui: &mut egui::Ui, root: &str, excludes: &PathRules, totals: &mut Vec<(Format, usize, u64)>) {
+    ui.horizontal(|ui| {
+        if ui.button("Refresh").clicked() {
+            *totals = self::totals(Path::new(root), excludes);
+        }
+        let size: u64 = totals.iter().map(|(_, _, size)| size).sum();
+        ui.label(format!("{} in archives below {}", crate::format_size(size as f64), root));
+    });
+    ui.separator();
+    egui::Grid::new("archive_totals").striped(true).show(ui, |ui| {
+        for heading in ["Format", "Files", "Size"] {
+            ui.strong(heading);
+        }
+        ui.end_row();
+        for (format, count, size) in totals.iter() {
+            ui.label(format.name());
+            ui.label(count.to_string());
+            ui.label(crate::format_size(*size as f64));
+            ui.end_row();
+        }
+    });
+}
+
+//one file or directory stored in an archive
+pub struct ArchiveEntry {
+    //relative path inside the archive, without a trailing slash
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub compressed: u64,
+}
+
+//an archive's table of contents, read without extracting anything
+pub struct Listing {
+    pub archive: PathBuf,
+    pub entries: Vec<ArchiveEntry>,
+    //a .tar.gz is compressed as a whole, so its entries' compressed sizes are their share of it
+    pub estimated: bool,
+}
+
+//what a sector of an archive's virtual directory holds
+pub struct ArchiveChild {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub compressed: u64,
+}
+
+fn browsable(format: Format) -> bool {
+    matches!(format, Format::Zip | Format::Tar | Format::Gzip)
+}
+
+//splits a path like /home/me/backup.zip/photos/2020 into the archive and the path inside it
+pub fn split(path: &Path) -> Option<(PathBuf, String)> {
+    let archive = path.ancestors().find(|ancestor| ancestor.is_file())?;
+    if !detect(archive).map(browsable).unwrap_or(false) {
+        return None;
+    }
+    let inner = path.strip_prefix(archive).ok()?.to_string_lossy().to_string();
+    Some((archive.to_path_buf(), inner))
+}
+
+fn read_zip(file: File) -> Result<Vec<ArchiveEntry>, std::io::Error> {
+    let mut zip = zip::ZipArchive::new(BufReader::new(file)).map_err(std::io::Error::other)?;
+    let mut entries = Vec::new();
+    for index in 0..zip.len() {
+        //raw access only reads the central directory record, nothing is inflated
+        let file = zip.by_index_raw(index).map_err(std::io::Error::other)?;
+        entries.push(ArchiveEntry {
+            path: file.name().trim_end_matches('/').to_string(),
+            is_dir: file.is_dir(),
+            size: file.size(),
+            compressed: file.compressed_size(),
+        });
+    }
+    Ok(entries)
+}
+
+//tar headers are read in order, so a .tar.gz is decompressed in memory and the data skipped
+fn read_tar(reader: impl Read) -> Result<Vec<ArchiveEntry>, std::io::Error> {
+    let mut tar = tar::Archive::new(reader);
+    let mut entries = Vec::new();
+    for entry in tar.entries()? {
+        let entry = entry?;
+        let path = entry.path()?.to_string_lossy().trim_start_matches("./").trim_end_matches('/').to_string();
+        if path.is_empty() || path == "." {
+            continue;
+        }
+        let is_dir = entry.header().entry_type().is_dir();
+        let size = if is_dir { 0 } else { entry.size() };
+        entries.push(ArchiveEntry { path, is_dir, size, compressed: size });
+    }
+    Ok(entries)
+}
+
+pub fn read_listing(archive: &Path) -> Result<Listing, std::io::Error> {
+    let file = File::open(archive)?;
+    let archive_size = file.metadata()?.len();
+    let (mut entries, estimated) = match detect(archive) {
+        Some(Format::Zip) => (read_zip(file)?, false),
+        Some(Format::Tar) => (read_tar(BufReader::new(file))?, false),
+        Some(Format::Gzip) => (read_tar(GzDecoder::new(BufReader::new(file)))?, true),
+        _ => return Err(std::io::Error::from(std::io::ErrorKind::Unsupported)),
+    };
+    if estimated {
+        let total: u64 = entries.iter().map(|entry| entry.size).sum();
+        for entry in entries.iter_mut() {
+            entry.compressed = if total == 0 { 0 } else { (entry.size as f64 / total as f64 * archive_size as f64) as u64 };
+        }
+    }
+    Ok(Listing { archive: archive.to_path_buf(), entries, estimated })
+}
+
+impl Listing {
+    pub fn is_file(&self, inner: &str) -> bool {
+        self.entries.iter().any(|entry| entry.path == inner && !entry.is_dir)
+    }
+
+    //the direct children of a directory inside the archive, with the sizes of everything below them
+    pub fn children(&self, inner: &str) -> Vec<ArchiveChild> {
+        let prefix = if inner.is_empty() { String::new() } else { format!("{}/", inner) };
+        let mut children: Vec<ArchiveChild> = Vec::new();
+        for entry in &self.entries {
+            let Some(rest) = entry.path.strip_prefix(&prefix) else { continue };
+            if rest.is_empty() {
+                continue;
+            }
+            //archives don't always list the directories themselves, only the files in them
+            let (name, is_dir) = match rest.split_once('/') {
+                Some((name, _)) => (name, true),
+                None => (rest, entry.is_dir),
+            };
+            let index = match children.iter().position(|child| child.name == name) {
+                Some(index) => index,
+                None => {
+                    children.push(ArchiveChild { name: name.to_string(), is_dir, size: 0, compressed: 0 });
+                    children.len() - 1
+                }
+            };
+            children[index].size += entry.size;
+            children[index].compressed += entry.compressed;
+        }
+        children
+    }
+}
+
+fn ratio(size: u64, compressed: u64) -> String {
+    if compressed == 0 { "-".to_string() } else { format!("{:.1}x", size as f64 / compressed as f64) }
+}
+
+//table of the shown archive directory next to the chart
+pub fn show_children(ui: &mut egui::Ui, listing: &Listing, children: &[ArchiveChild]) {
+    let size: u64 = children.iter().map(|child| child.size).sum();
+    let compressed: u64 = children.iter().map(|child| child.compressed).sum();
+    ui.label(format!(
+        "Inside {}: {} uncompressed, {} compressed{}, ratio {}",
+        listing.archive.display(),
+        crate::format_size(size as f64),
+        crate::format_size(compressed as f64),
+        if listing.estimated { " (estimated)" } else { "" },
+        ratio(size, compressed)
+    ));
+    egui::ScrollArea::vertical().id_source("archive_children").max_height(150.0).show(ui, |ui| {
+        egui::Grid::new("archive_children").striped(true).show(ui, |ui| {
+            for heading in ["Name", "Uncompressed", "Compressed", "Ratio"] {
+                ui.strong(heading);
+            }
+            ui.end_row();
+            for child in children {
+                ui.label(if child.is_dir { format!("{}/", child.name) } else { child.name.clone() });
+                ui.label(crate::format_size(child.size as f64));
+                ui.label(crate::format_size(child.compressed as f64));
+                ui.label(ratio(child.size, child.compressed));
+                ui.end_row();
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, is_dir: bool, size: u64, compressed: u64) -> ArchiveEntry {
+        ArchiveEntry { path: path.to_string(), is_dir, size, compressed }
+    }
+
+    fn listing() -> Listing {
+        Listing {
+            archive: PathBuf::from("/tmp/backup.zip"),
+            entries: vec![
+                entry("docs", true, 0, 0),
+                entry("docs/a.txt", false, 100, 40),
+                entry("docs/sub/b.txt", false, 50, 20),
+                //a directory that is only implied by the files in it
+                entry("photos/1.jpg", false, 1000, 990),
+                entry("readme", false, 10, 10),
+            ],
+            estimated: false,
+        }
+    }
+
+    #[test]
+    fn children_of_the_root_add_up_everything_below_them() {
+        let children = listing().children("");
+        let summary: Vec<(&str, bool, u64, u64)> = children.iter().map(|child| (child.name.as_str(), child.is_dir, child.size, child.compressed)).collect();
+        assert_eq!(summary, vec![("docs", true, 150, 60), ("photos", true, 1000, 990), ("readme", false, 10, 10)]);
+    }
+
+    #[test]
+    fn children_of_a_subdirectory() {
+        let children = listing().children("docs");
+        let summary: Vec<(&str, bool, u64)> = children.iter().map(|child| (child.name.as_str(), child.is_dir, child.size)).collect();
+        assert_eq!(summary, vec![("a.txt", false, 100), ("sub", true, 50)]);
+    }
+
+    #[test]
+    fn prefixes_only_match_whole_components() {
+        let mut listing = listing();
+        listing.entries.push(entry("docs2/c.txt", false, 7, 7));
+        assert_eq!(listing.children("docs").len(), 2);
+        assert!(listing.children("doc").is_empty());
+        assert!(listing.children("readme").is_empty());
+    }
+
+    #[test]
+    fn files_are_told_apart_from_directories() {
+        let listing = listing();
+        assert!(listing.is_file("docs/a.txt"));
+        assert!(!listing.is_file("docs"));
+        assert!(!listing.is_file("photos"));
+    }
+}
@@ -0,0 +1,174 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::mounts;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DeviceKind {
+    Disk,
+    Partition,
+    //device-mapper targets: LVM volumes, dm-crypt, multipath...
+    Mapper,
+    Loop,
+}
+
+pub struct BlockDevice {
+    pub name: String,
+    pub device_number: String,
+    pub size: u64,
+    pub kind: DeviceKind,
+    //model of a disk, name of a partition, volume name of a device-mapper device, backing file of a loop device
+    pub detail: String,
+    //devices a device-mapper device is built on
+    pub slaves: Vec<String>,
+    pub mount_points: Vec<PathBuf>,
+    pub partitions: Vec<BlockDevice>,
+}
+
+fn read_trimmed(path: &Path) -> String {
+    fs::read_to_string(path).map(|contents| contents.trim().to_string()).unwrap_or_default()
+}
+
+fn file_names(path: &Path) -> Vec<String> {
+    fs::read_dir(path)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|entry| entry.file_name().to_string_lossy().to_string()).collect())
+        .unwrap_or_default()
+}
+
+//rows of /proc/partitions: "major minor #blocks name", with sizes in KiB
+fn partitions_table() -> Vec<(String, u64, String)> {
+    fs::read_to_string("/proc/partitions")
+        .map(|table| {
+            table
+                .lines()
+                .skip(2)
+                .filter_map(|line| {
+                    let fields: Vec<&str> = line.split_whitespace().collect();
+                    if fields.len() != 4 {
+                        return None;
+                    }
+                    Some((format!("{}:{}", fields[0], fields[1]), fields[2].parse::<u64>().ok()? * 1024, fields[3].to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+//disks with their partitions nested, then device-mapper and loop devices, in /proc/partitions order
+pub fn layout() -> Vec<BlockDevice> {
+    let mounts = mounts::mounts();
+    let mut devices: Vec<BlockDevice> = Vec::new();
+    let mut partitions: Vec<(String, BlockDevice)> = Vec::new();
+    for (device_number, size, name) in partitions_table() {
+        let sys = Path::new("/sys/class/block").join(&name);
+        let kind = if sys.join("partition").exists() {
+            DeviceKind::Partition
+        } else if name.starts_with("dm-") {
+            DeviceKind::Mapper
+        } else if name.starts_with("loop") {
+            DeviceKind::Loop
+        } else {
+            DeviceKind::Disk
+        };
+        let detail = match kind {
+            DeviceKind::Disk => read_trimmed(&sys.join("device/model")),
+            //GPT partition names are only in the uevent file
+            DeviceKind::Partition => read_trimmed(&sys.join("uevent")).lines().find_map(|line| line.strip_prefix("PARTNAME=")).unwrap_or_default().to_string(),
+            DeviceKind::Mapper => read_trimmed(&sys.join("dm/name")),
+            DeviceKind::Loop => read_trimmed(&sys.join("loop/backing_file")),
+        };
+        //btrfs and some others report anonymous device numbers, so the mount source is checked too
+        let mut sources = vec![format!("/dev/{}", name)];
+        if kind == DeviceKind::Mapper {
+            sources.push(format!("/dev/mapper/{}", detail));
+        }
+        let mount_points = mounts
+            .iter()
+            .filter(|mount| mount.device_number == device_number || sources.contains(&mount.source))
+            .map(|mount| mount.mount_point.clone())
+            .collect();
+        let device = BlockDevice {
+            slaves: file_names(&sys.join("slaves")),
+            name,
+            device_number,
+            size,
+            kind,
+            detail,
+            mount_points,
+            partitions: Vec::new(),
+        };
+        if kind == DeviceKind::Partition {
+            //the partition's sysfs directory sits inside its disk's
+            let disk = fs::canonicalize(&sys).ok().and_then(|path| path.parent().and_then(|parent| parent.file_name()).map(|name| name.to_string_lossy().to_string()));
+            partitions.push((disk.unwrap_or_default(), device));
+        } else {
+            devices.push(device);
+        }
+    }
+    for (disk, partition) in partitions {
+        match devices.iter_mut().find(|device| device.name == disk) {
+            Some(device) => device.partitions.push(partition),
+            None => devices.push(partition),
+        }
+    }
+    devices.sort_by_key(|device| match device.kind {
+        DeviceKind::Disk | DeviceKind::Partition => 0,
+        DeviceKind::Mapper => 1,
+        DeviceKind::Loop => 2,
+    });
+    devices
+}
+
+fn device_row(ui: &mut egui::Ui, device: &BlockDevice, indent: &str, scan: &mut Option<PathBuf>) {
+    ui.label(format!("{}{}", indent, device.name)).on_hover_text(format!("Device {}", device.device_number));
+    ui.label(crate::format_size(device.size as f64));
+    let detail = if device.slaves.is_empty() {
+        device.detail.clone()
+    } else {
+        format!("{} on {}", device.detail, device.slaves.join(", "))
+    };
+    ui.label(detail);
+    ui.horizontal(|ui| {
+        for mount_point in &device.mount_points {
+            if ui.link(mount_point.to_string_lossy()).on_hover_text("Scan this filesystem").clicked() {
+                *scan = Some(mount_point.clone());
+            }
+        }
+    });
+    ui.end_row();
+}
+
+//panel of the storage stack, returns the mount point to scan when one is clicked
+pub fn show(ui: &mut egui::Ui, devices: &mut Vec<BlockDevice>) -> Option<PathBuf> {
+    let mut scan = None;
+    if ui.button("Refresh").clicked() {
+        *devices = layout();
+    }
+    ui.separator();
+    egui::ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+        for (title, kinds) in [
+            ("Disks and partitions", [DeviceKind::Disk, DeviceKind::Partition]),
+            ("Device mapper and LVM", [DeviceKind::Mapper, DeviceKind::Mapper]),
+            ("Loop devices", [DeviceKind::Loop, DeviceKind::Loop]),
+        ] {
+            let section: Vec<&BlockDevice> = devices.iter().filter(|device| kinds.contains(&device.kind)).collect();
+            if section.is_empty() {
+                continue;
+            }
+            egui::CollapsingHeader::new(title).default_open(true).show(ui, |ui| {
+                egui::Grid::new(title).striped(true).show(ui, |ui| {
+                    for heading in ["Device", "Size", "", "Mounted on"] {
+                        ui.strong(heading);
+                    }
+                    ui.end_row();
+                    for device in section {
+                        device_row(ui, device, "", &mut scan);
+                        for partition in &device.partitions {
+                            device_row(ui, partition, "  ", &mut scan);
+                        }
+                    }
+                });
+            });
+        }
+    });
+    scan
+}
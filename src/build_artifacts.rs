@@ -0,0 +1,252 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+use crate::path_rules::{protected_hint, PathRules};
+
+//directory name, and the files next to it that make it a build output rather than someone's data
+const ARTIFACT_DIRS: [(&str, &[&str]); 5] = [
+    ("target", &["Cargo.toml"]),
+    ("node_modules", &["package.json"]),
+    (".gradle", &["build.gradle", "build.gradle.kts", "settings.gradle", "settings.gradle.kts"]),
+    ("build", &["build.gradle", "build.gradle.kts", "CMakeLists.txt", "setup.py", "pyproject.toml"]),
+    ("__pycache__", &[]),
+];
+
+//files that mark the top of a python project, so its __pycache__ dirs are reported together
+const PYTHON_PROJECT_MARKERS: [&str; 4] = ["pyproject.toml", "setup.py", "setup.cfg", "requirements.txt"];
+
+#[derive(Clone)]
+pub struct Artifact {
+    pub path: PathBuf,
+    pub kind: String,
+    pub size: u64,
+    pub last_modified: Option<SystemTime>,
+}
+
+#[derive(Clone)]
+pub struct ArtifactProject {
+    pub root: PathBuf,
+    pub artifacts: Vec<Artifact>,
+}
+
+impl ArtifactProject {
+    pub fn size(&self) -> u64 {
+        self.artifacts.iter().map(|artifact| artifact.size).sum()
+    }
+
+    //newest file inside any of the artifacts, which is when the project was last built
+    pub fn last_build(&self) -> Option<SystemTime> {
+        self.artifacts.iter().filter_map(|artifact| artifact.last_modified).max()
+    }
+
+    pub fn is_stale(&self, days: u64) -> bool {
+        let cutoff = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+        self.last_build().map(|built| built < cutoff).unwrap_or(true)
+    }
+
+    //the protected entry covering one of its artifacts, which keeps the project from being cleaned
+    pub fn protected_by<'a>(&self, path_rules: &'a PathRules) -> Option<&'a str> {
+        self.artifacts.iter().find_map(|artifact| path_rules.protecting(&artifact.path))
+    }
+
+    pub fn kinds(&self) -> String {
+        let mut kinds: Vec<&str> = self.artifacts.iter().map(|artifact| artifact.kind.as_str()).collect();
+        kinds.sort();
+        kinds.dedup();
+        kinds.join(", ")
+    }
+}
+
+fn artifact_kind(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let parent = path.parent()?;
+    //virtualenvs can have any name but always carry a pyvenv.cfg
+    if path.join("pyvenv.cfg").is_file() {
+        return Some("virtualenv".to_string());
+    }
+    let (_, markers) = ARTIFACT_DIRS.iter().find(|(dir, _)| *dir == name)?;
+    if name == "__pycache__" || markers.iter().any(|marker| parent.join(marker).exists()) {
+        Some(name.to_string())
+    } else {
+        None
+    }
+}
+
+//a build directory checked into version control is source, not output
+fn is_tracked(path: &Path) -> bool {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else { return false };
+    if !parent.ancestors().any(|dir| dir.join(".git").exists()) {
+        return false;
+    }
+    match Command::new("git").arg("-C").arg(parent).args(["ls-files", "--"]).arg(name).output() {
+        Ok(output) if output.status.success() => !output.stdout.is_empty(),
+        //when git can't tell, nothing inside the repository is assumed safe to delete
+        _ => true,
+    }
+}
+
+fn project_root(artifact: &Path, kind: &str, scan_root: &Path) -> PathBuf {
+    let parent = artifact.parent().unwrap_or(artifact).to_path_buf();
+    if kind != "__pycache__" {
+        return parent;
+    }
+    parent
+        .ancestors()
+        .take_while(|dir| dir.starts_with(scan_root))
+        .find(|dir| PYTHON_PROJECT_MARKERS.iter().any(|marker| dir.join(marker).exists()))
+        .map(|dir| dir.to_path_buf())
+        .unwrap_or(parent)
+}
+
+fn measure(path: &Path) -> (u64, Option<SystemTime>) {
+    let mut size = 0;
+    let mut newest = None;
+    for metadata in WalkDir::new(path).into_iter().filter_map(|e| e.ok()).filter_map(|e| e.metadata().ok()) {
+        if metadata.is_file() {
+            size += metadata.len();
+        }
+        if let Ok(modified) = metadata.modified() {
+            newest = newest.max(Some(modified));
+        }
+    }
+    (size, newest)
+}
+
+//every project under root with build outputs, biggest first; artifact dirs themselves are not descended into
+pub fn find_projects(root: &Path) -> Vec<ArtifactProject> {
+    let mut projects: Vec<ArtifactProject> = Vec::new();
+    let mut walker = WalkDir::new(root).into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else { continue };
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let Some(kind) = artifact_kind(entry.path()) else { continue };
+        walker.skip_current_dir();
+        if is_tracked(entry.path()) {
+            continue;
+        }
+        let (size, last_modified) = measure(entry.path());
+        let artifact = Artifact { path: entry.path().to_path_buf(), kind: kind.clone(), size, last_modified };
+        let project_root = project_root(entry.path(), &kind, root);
+        match projects.iter_mut().find(|project| project.root == project_root) {
+            Some(project) => project.artifacts.push(artifact),
+            None => projects.push(ArtifactProject { root: project_root, artifacts: vec![artifact] }),
+        }
+    }
+    projects.sort_by_key(|project| std::cmp::Reverse(project.size()));
+    projects
+}
+
+//build outputs can be regenerated, so they are deleted outright instead of trashed
+pub fn clean(project: &ArtifactProject, path_rules: &PathRules) -> Result<(), std::io::Error> {
+    for artifact in &project.artifacts {
+        if path_rules.protecting(&artifact.path).is_some() {
+            continue;
+        }
+        crate::trash::delete_permanently(&artifact.path)?;
+    }
+    Ok(())
+}
+
+pub struct ArtifactsPanel {
+    pub projects: Vec<ArtifactProject>,
+    pub stale_days: u64,
+    //roots of the projects to clean once the user confirms
+    confirming: Vec<PathBuf>,
+}
+
+impl Default for ArtifactsPanel {
+    fn default() -> Self {
+        Self { projects: Vec::new(), stale_days: 30, confirming: Vec::new() }
+    }
+}
+
+impl ArtifactsPanel {
+    //one row per project with a clean button, and cleaning of every stale project at once; both ask first
+    pub fn show(&mut self, ui: &mut egui::Ui, root: &str, path_rules: &PathRules) {
+        ui.horizontal(|ui| {
+            if ui.button("Find build artifacts").clicked() {
+                self.projects = find_projects(Path::new(root));
+                self.confirming.clear();
+            }
+            ui.label(format!("in {}", root));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Stale after");
+            ui.add(egui::DragValue::new(&mut self.stale_days).clamp_range(1..=3650));
+            ui.label("days");
+            let stale: Vec<&ArtifactProject> = self
+                .projects
+                .iter()
+                .filter(|project| project.is_stale(self.stale_days) && project.protected_by(path_rules).is_none())
+                .collect();
+            let stale_size: u64 = stale.iter().map(|project| project.size()).sum();
+            if ui
+                .add_enabled(!stale.is_empty(), egui::Button::new(format!("Clean {} stale projects ({})", stale.len(), crate::format_size(stale_size as f64))))
+                .clicked()
+            {
+                self.confirming = stale.iter().map(|project| project.root.clone()).collect();
+            }
+        });
+        if !self.confirming.is_empty() {
+            self.confirm(ui, path_rules);
+        }
+        ui.separator();
+        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            egui::Grid::new("build_artifacts").striped(true).show(ui, |ui| {
+                ui.strong("Project");
+                ui.strong("Artifacts");
+                ui.strong("Size");
+                ui.strong("Last build");
+                ui.end_row();
+                for project in &self.projects {
+                    ui.label(project.root.to_string_lossy());
+                    ui.label(project.kinds());
+                    ui.label(crate::format_size(project.size() as f64));
+                    ui.label(project.last_build().map(crate::format_age).unwrap_or_default());
+                    let protected = project.protected_by(path_rules);
+                    let clean = ui
+                        .add_enabled(protected.is_none(), egui::Button::new("Clean"))
+                        .on_disabled_hover_text(protected_hint(protected.unwrap_or_default()));
+                    if clean.clicked() {
+                        self.confirming = vec![project.root.clone()];
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    fn confirm(&mut self, ui: &mut egui::Ui, path_rules: &PathRules) {
+        let chosen: Vec<usize> = (0..self.projects.len()).filter(|i| self.confirming.contains(&self.projects[*i].root)).collect();
+        let size: u64 = chosen.iter().map(|i| self.projects[*i].size()).sum();
+        let mut done = false;
+        let mut cancelled = false;
+        ui.group(|ui| {
+            match chosen.as_slice() {
+                [i] => ui.label(format!("Delete the build outputs of {:?} ({})?", self.projects[*i].root, crate::format_size(size as f64))),
+                _ => ui.label(format!("Delete the build outputs of {} projects ({})?", chosen.len(), crate::format_size(size as f64))),
+            };
+            ui.colored_label(egui::Color32::RED, "They are deleted permanently, building the projects again recreates them.");
+            ui.horizontal(|ui| {
+                done = ui.button("Clean").clicked();
+                cancelled = ui.button("Cancel").clicked();
+            });
+        });
+        if done {
+            for i in chosen.into_iter().rev() {
+                match clean(&self.projects[i], path_rules) {
+                    Ok(()) => {
+                        self.projects.remove(i);
+                    }
+                    Err(e) => eprintln!("Error cleaning {:?}: {:?}", self.projects[i].root, e),
+                }
+            }
+        }
+        if done || cancelled {
+            self.confirming.clear();
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use crate::path_rules::{protected_hint, PathRules};
+
+//known cache locations ("~/" is the home directory) and the tool's own cleanup command, if it has one;
+//entries without a command are cleared by removing what is inside them
+const CATALOG: [(&str, &str, Option<&str>); 17] = [
+    ("Application caches", "~/.cache", None),
+    ("pip", "~/.cache/pip", Some("pip cache purge")),
+    ("Yarn", "~/.cache/yarn", Some("yarn cache clean")),
+    ("Go build cache", "~/.cache/go-build", Some("go clean -cache")),
+    ("Thumbnails", "~/.cache/thumbnails", None),
+    ("Cargo registry", "~/.cargo/registry", Some("cargo cache --autoclean")),
+    ("Cargo git checkouts", "~/.cargo/git", Some("cargo cache --autoclean")),
+    ("npm", "~/.npm", Some("npm cache clean --force")),
+    ("Maven repository", "~/.m2/repository", None),
+    ("Gradle caches", "~/.gradle/caches", None),
+    ("Go modules", "~/go/pkg/mod", Some("go clean -modcache")),
+    ("Conda packages", "~/.conda/pkgs", Some("conda clean --all --yes")),
+    ("Composer", "~/.composer/cache", Some("composer clear-cache")),
+    ("apt archives", "/var/cache/apt/archives", Some("sudo apt-get clean")),
+    ("dnf", "/var/cache/dnf", Some("sudo dnf clean all")),
+    ("pacman packages", "/var/cache/pacman/pkg", Some("sudo paccache -r")),
+    ("zypper packages", "/var/cache/zypp/packages", Some("sudo zypper clean --all")),
+];
+
+#[derive(Clone)]
+pub struct CacheEntry {
+    pub name: String,
+    pub path: PathBuf,
+    pub command: Option<String>,
+    //bytes inside path, not counting other catalog entries nested in it
+    pub size: u64,
+}
+
+impl CacheEntry {
+    //commands run as root can't be started from here, only copied
+    pub fn needs_root(&self) -> bool {
+        self.command.as_deref().map(|command| command.starts_with("sudo ")).unwrap_or(false)
+    }
+}
+
+pub fn expand(path: &str) -> Option<PathBuf> {
+    match path.strip_prefix("~/") {
+        Some(relative) => dirs::home_dir().map(|home| home.join(relative)),
+        None => Some(PathBuf::from(path)),
+    }
+}
+
+//every catalog entry that exists on this machine, biggest first
+pub fn find_caches() -> Vec<CacheEntry> {
+    let mut entries: Vec<CacheEntry> = CATALOG
+        .iter()
+        .filter_map(|(name, path, command)| {
+            let path = expand(path)?;
+            path.is_dir().then(|| CacheEntry {
+                name: name.to_string(),
+                size: crate::directory_size(&path),
+                path,
+                command: command.map(|command| command.to_string()),
+            })
+        })
+        .collect();
+    //~/.cache contains pip, yarn and others, which are already reported on their own
+    let nested: Vec<(PathBuf, u64)> = entries.iter().map(|entry| (entry.path.clone(), entry.size)).collect();
+    for entry in entries.iter_mut() {
+        for (path, size) in &nested {
+            if path != &entry.path && path.starts_with(&entry.path) {
+                entry.size = entry.size.saturating_sub(*size);
+            }
+        }
+    }
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.size));
+    entries
+}
+
+//removes everything inside a cache directory except the keep paths, but keeps the directory itself
+pub fn clear_directory(path: &Path, keep: &[PathBuf]) -> Result<(), std::io::Error> {
+    for entry in fs::read_dir(path)? {
+        let entry_path = entry?.path();
+        if !keep.contains(&entry_path) {
+            crate::trash::delete_permanently(&entry_path)?;
+        }
+    }
+    Ok(())
+}
+
+//catalog entries inside another one, which clearing the outer one leaves alone
+pub fn nested_in(entries: &[CacheEntry], path: &Path) -> Vec<PathBuf> {
+    entries
+        .iter()
+        .map(|entry| entry.path.clone())
+        .filter(|nested| nested != path && nested.starts_with(path))
+        .collect()
+}
+
+pub fn run_command(command: &str) -> Result<(), std::io::Error> {
+    let output = Command::new("sh").arg("-c").arg(command).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(String::from_utf8_lossy(&output.stderr).to_string()))
+    }
+}
+
+//shared with the thread running a cleanup command
+#[derive(Default)]
+struct CommandRun {
+    running: Option<String>,
+    finished: bool,
+    error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct CachesPanel {
+    pub entries: Vec<CacheEntry>,
+    //path of the cache to clean once the user confirms
+    confirming: Option<PathBuf>,
+    run: Arc<Mutex<CommandRun>>,
+}
+
+impl CachesPanel {
+    //cleanup commands can take minutes, so they run on their own thread and the panel shows how they ended
+    fn start(&self, command: String, ctx: egui::Context) {
+        let run = self.run.clone();
+        {
+            let mut state = run.lock().unwrap();
+            state.running = Some(command.clone());
+            state.error = None;
+        }
+        std::thread::spawn(move || {
+            let result = run_command(&command);
+            let mut state = run.lock().unwrap();
+            state.running = None;
+            state.finished = true;
+            if let Err(e) = result {
+                state.error = Some(format!("{} failed: {}", command, e));
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    //the size of every known cache with its cleanup command or a clear button; both ask first
+    pub fn show(&mut self, ui: &mut egui::Ui, path_rules: &PathRules) {
+        let (running, finished, error) = {
+            let mut state = self.run.lock().unwrap();
+            (state.running.clone(), std::mem::take(&mut state.finished), state.error.clone())
+        };
+        let mut refresh = finished;
+        ui.horizontal(|ui| {
+            refresh |= ui.add_enabled(running.is_none(), egui::Button::new("Measure caches")).clicked();
+            let total: u64 = self.entries.iter().map(|entry| entry.size).sum();
+            ui.label(format!("{} caches, {}", self.entries.len(), crate::format_size(total as f64)));
+        });
+        if let Some(command) = &running {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(format!("Running {}...", command));
+            });
+        }
+        if let Some(error) = error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        if self.confirming.is_some() {
+            self.confirm(ui);
+        }
+        ui.separator();
+        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            egui::Grid::new("caches").striped(true).show(ui, |ui| {
+                for entry in self.entries.iter() {
+                    ui.label(&entry.name);
+                    ui.label(entry.path.to_string_lossy());
+                    ui.label(crate::format_size(entry.size as f64));
+                    let protected = path_rules.protecting(&entry.path);
+                    match &entry.command {
+                        Some(command) => {
+                            ui.code(command);
+                            if entry.needs_root() {
+                                if ui.button("Copy").clicked() {
+                                    ui.output_mut(|output| output.copied_text = command.clone());
+                                }
+                            } else if ui
+                                .add_enabled(protected.is_none() && running.is_none(), egui::Button::new("Run"))
+                                .on_disabled_hover_text(protected.map(protected_hint).unwrap_or_else(|| "Another command is running.".to_string()))
+                                .clicked()
+                            {
+                                self.confirming = Some(entry.path.clone());
+                            }
+                        }
+                        None => {
+                            ui.label("");
+                            let clear = ui
+                                .add_enabled(protected.is_none(), egui::Button::new("Clear"))
+                                .on_disabled_hover_text(protected_hint(protected.unwrap_or_default()));
+                            if clear.clicked() {
+                                self.confirming = Some(entry.path.clone());
+                            }
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        if refresh {
+            self.entries = find_caches();
+        }
+    }
+
+    fn confirm(&mut self, ui: &mut egui::Ui) {
+        let Some(entry) = self.entries.iter().find(|entry| Some(&entry.path) == self.confirming.as_ref()).cloned() else {
+            self.confirming = None;
+            return;
+        };
+        let size = crate::format_size(entry.size as f64);
+        let mut done = false;
+        let mut cancelled = false;
+        ui.group(|ui| {
+            match &entry.command {
+                Some(command) => {
+                    ui.label(format!("Clean the {} cache ({}) by running:", entry.name, size));
+                    ui.code(command);
+                }
+                None => {
+                    ui.label(format!("Delete everything inside {:?} ({})?", entry.path, size));
+                    ui.colored_label(egui::Color32::RED, "It is deleted permanently, other caches inside it are kept.");
+                }
+            }
+            ui.horizontal(|ui| {
+                done = ui.button(if entry.command.is_some() { "Run" } else { "Clear" }).clicked();
+                cancelled = ui.button("Cancel").clicked();
+            });
+        });
+        if done {
+            match &entry.command {
+                Some(command) => self.start(command.clone(), ui.ctx().clone()),
+                None => {
+                    let result = clear_directory(&entry.path, &nested_in(&self.entries, &entry.path));
+                    self.run.lock().unwrap().error = result.err().map(|e| format!("Clearing {:?} failed: {}", entry.path, e));
+                    self.entries = find_caches();
+                }
+            }
+        }
+        if done || cancelled {
+            self.confirming = None;
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use flate2::write::GzEncoder;
+use walkdir::WalkDir;
+use crate::path_rules::PathRules;
+
+//how much of each sampled file is read and compressed
+const SAMPLE_BYTES: u64 = 256 * 1024;
+//files sampled at most, spread evenly over the directory
+const MAX_SAMPLES: usize = 200;
+const ZSTD_LEVEL: i32 = 3;
+
+//media formats that are compressed already but have no archive signature
+const COMPRESSED_MEDIA: [&str; 24] = [
+    "jpg", "jpeg", "png", "gif", "webp", "heic", "avif", "mp3", "aac", "ogg", "opus", "flac", "m4a", "mp4", "m4v", "mkv", "webm", "avi", "mov",
+    "docx", "xlsx", "pptx", "odt", "pdf",
+];
+
+fn already_compressed(path: &Path) -> bool {
+    let extension = path.extension().map(|extension| extension.to_string_lossy().to_lowercase()).unwrap_or_default();
+    COMPRESSED_MEDIA.contains(&extension.as_str()) || crate::archives::detect(path).is_some()
+}
+
+//projected sizes of a directory if its compressible files were compressed
+pub struct Estimate {
+    pub path: PathBuf,
+    pub files: usize,
+    //bytes of files that would be compressed
+    pub size: u64,
+    pub skipped_files: usize,
+    //bytes of files left as they are because they are compressed already
+    pub skipped_size: u64,
+    pub sampled: usize,
+    pub sampled_bytes: u64,
+    pub zstd_size: u64,
+    pub gzip_size: u64,
+}
+
+impl Estimate {
+    pub fn total(&self) -> u64 {
+        self.size + self.skipped_size
+    }
+}
+
+fn read_sample(path: &Path) -> Option<Vec<u8>> {
+    let mut sample = Vec::new();
+    crate::open_without_atime(path).ok()?.take(SAMPLE_BYTES).read_to_end(&mut sample).ok()?;
+    Some(sample)
+}
+
+fn gzip_len(data: &[u8]) -> Option<u64> {
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).ok()?;
+    Some(encoder.finish().ok()?.len() as u64)
+}
+
+//compresses the start of up to MAX_SAMPLES files in memory and scales each file's ratio to its full size
+pub fn estimate(root: &Path, excludes: &PathRules) -> Estimate {
+    let mut estimate = Estimate {
+        path: root.to_path_buf(),
+        files: 0,
+        size: 0,
+        skipped_files: 0,
+        skipped_size: 0,
+        sampled: 0,
+        sampled_bytes: 0,
+        zstd_size: 0,
+        gzip_size: 0,
+    };
+    let mut candidates: Vec<(PathBuf, u64)> = Vec::new();
+    let walker = WalkDir::new(root).into_iter().filter_entry(|entry| entry.depth() == 0 || !excludes.is_excluded(entry.path()));
+    for entry in walker.filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let size = entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        if already_compressed(entry.path()) {
+            estimate.skipped_files += 1;
+            estimate.skipped_size += size;
+        } else if size > 0 {
+            estimate.files += 1;
+            estimate.size += size;
+            candidates.push((entry.into_path(), size));
+        }
+    }
+
+    //the ratio of the sampled bytes stands in for everything that wasn't sampled
+    let step = candidates.len().div_ceil(MAX_SAMPLES).max(1);
+    let (mut sampled_size, mut zstd_projected, mut gzip_projected) = (0u64, 0.0, 0.0);
+    for (path, size) in candidates.iter().step_by(step).take(MAX_SAMPLES) {
+        let Some(sample) = read_sample(path) else { continue };
+        if sample.is_empty() {
+            continue;
+        }
+        let (Ok(zstd), Some(gzip)) = (zstd::bulk::compress(&sample, ZSTD_LEVEL), gzip_len(&sample)) else { continue };
+        let scale = *size as f64 / sample.len() as f64;
+        zstd_projected += zstd.len() as f64 * scale;
+        gzip_projected += gzip as f64 * scale;
+        sampled_size += size;
+        estimate.sampled += 1;
+        estimate.sampled_bytes += sample.len() as u64;
+    }
+    if sampled_size > 0 {
+        let scale = estimate.size as f64 / sampled_size as f64;
+        estimate.zstd_size = (zstd_projected * scale) as u64;
+        estimate.gzip_size = (gzip_projected * scale) as u64;
+    } else {
+        estimate.zstd_size = estimate.size;
+        estimate.gzip_size = estimate.size;
+    }
+    estimate
+}
+
+//the projection for the last estimated directory, with a button to run it again
+pub fn show(ui: &mut egui::Ui, estimate: &mut Option<Estimate>, excludes: &PathRules) {
+    let Some(current) = estimate else {
+        ui.label("Right-click a directory and choose \"Estimate compression\".");
+        return;
+    };
+    ui.horizontal(|ui| {
+        ui.label(format!("{}", current.path.display()));
+        if ui.button("Refresh").clicked() {
+            *current = self::estimate(&current.path, excludes);
+        }
+    });
+    ui.label(format!(
+        "{} in {} files, {} of them sampled ({} read)",
+        crate::format_size(current.size as f64),
+        current.files,
+        current.sampled,
+        crate::format_size(current.sampled_bytes as f64)
+    ));
+    ui.label(format!(
+        "Skipped {} already compressed files ({}), counted at their current size",
+        current.skipped_files,
+        crate::format_size(current.skipped_size as f64)
+    ));
+    ui.separator();
+    egui::Grid::new("compressibility").striped(true).show(ui, |ui| {
+        for heading in ["Method", "Projected size", "Savings", "Ratio"] {
+            ui.strong(heading);
+        }
+        ui.end_row();
+        for (method, compressed) in [(format!("zstd -{}", ZSTD_LEVEL), current.zstd_size), ("gzip -6".to_string(), current.gzip_size)] {
+            let projected = compressed + current.skipped_size;
+            let savings = current.total().saturating_sub(projected);
+            ui.label(method);
+            ui.label(crate::format_size(projected as f64));
+            ui.label(format!(
+                "{} ({:.0}%)",
+                crate::format_size(savings as f64),
+                if current.total() == 0 { 0.0 } else { savings as f64 / current.total() as f64 * 100.0 }
+            ));
+            ui.label(if compressed == 0 { "-".to_string() } else { format!("{:.1}x", current.size as f64 / compressed as f64) });
+            ui.end_row();
+        }
+    });
+}
@@ -0,0 +1,535 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::hash::Hasher;
+use std::io::{BufReader, ErrorKind, Read};
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use walkdir::WalkDir;
+use crate::path_rules::{protected_hint, PathRules};
+
+//only this much of each file is hashed in the second stage
+const PARTIAL_HASH_BYTES: usize = 4096;
+
+#[derive(Clone)]
+pub struct DuplicateGroup {
+    pub size: u64,
+    pub paths: Vec<PathBuf>,
+    //which copies survive "trash the rest", the first one by default
+    pub keep: Vec<bool>,
+}
+
+impl DuplicateGroup {
+    fn new(size: u64, paths: Vec<PathBuf>) -> Self {
+        let keep = (0..paths.len()).map(|i| i == 0).collect();
+        Self { size, paths, keep }
+    }
+
+    //bytes freed if only one copy stayed
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.paths.len() as u64 - 1)
+    }
+}
+
+//shared between the finder thread and the panel
+#[derive(Clone, Default)]
+pub struct DuplicateScan {
+    pub running: bool,
+    pub stage: String,
+    pub done: usize,
+    pub total: usize,
+    pub groups: Vec<DuplicateGroup>,
+    //dry run of a link action, waiting to be applied or cancelled
+    pub link_plan: Option<LinkPlan>,
+    //unkept copies to trash, waiting to be confirmed or cancelled
+    pub trash_plan: Option<TrashPlan>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LinkKind {
+    HardLink,
+    //copy-on-write clone through the FICLONE ioctl, only on filesystems like btrfs and xfs
+    Reflink,
+}
+
+impl LinkKind {
+    fn describe(&self) -> &str {
+        match self {
+            LinkKind::HardLink => "hard links",
+            LinkKind::Reflink => "reflinks",
+        }
+    }
+}
+
+//what replacing the unkept copies with links to the first kept one would do
+#[derive(Clone)]
+pub struct LinkPlan {
+    pub kind: LinkKind,
+    pub links: Vec<(PathBuf, PathBuf)>,
+    pub bytes: u64,
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+pub fn plan_links(groups: &[DuplicateGroup], kind: LinkKind, path_rules: &PathRules) -> LinkPlan {
+    let mut plan = LinkPlan { kind, links: Vec::new(), bytes: 0, skipped: Vec::new() };
+    for group in groups {
+        let Some(source) = group.paths.iter().zip(&group.keep).find(|(_, keep)| **keep).map(|(path, _)| path) else {
+            continue;
+        };
+        let Ok(source_metadata) = fs::metadata(source) else { continue };
+        for (path, keep) in group.paths.iter().zip(&group.keep) {
+            if *keep {
+                continue;
+            }
+            if let Some(protected) = path_rules.protecting(path) {
+                plan.skipped.push((path.clone(), format!("protected by {}", protected)));
+                continue;
+            }
+            let Ok(metadata) = fs::metadata(path) else {
+                plan.skipped.push((path.clone(), "can't be read".to_string()));
+                continue;
+            };
+            //neither kind of link can cross filesystems
+            if metadata.dev() != source_metadata.dev() {
+                plan.skipped.push((path.clone(), "on another filesystem".to_string()));
+                continue;
+            }
+            //a hard link shares the source's inode, so the copy would silently take its mode and owner;
+            //a reflink keeps the mode but is a new file owned by whoever makes it
+            let changes_access = match kind {
+                LinkKind::HardLink => (metadata.mode(), metadata.uid(), metadata.gid()) != (source_metadata.mode(), source_metadata.uid(), source_metadata.gid()),
+                LinkKind::Reflink => metadata.uid() != unsafe { libc::geteuid() },
+            };
+            if changes_access {
+                plan.skipped.push((path.clone(), "its owner or permissions would change".to_string()));
+                continue;
+            }
+            plan.links.push((source.clone(), path.clone()));
+            plan.bytes += group.size;
+        }
+    }
+    plan
+}
+
+//contents are compared byte for byte right before linking, since the hashes could collide or the files changed since the scan
+//protection is checked again, since paths may have been protected after the dry run
+pub fn apply_links(plan: &LinkPlan, groups: &mut Vec<DuplicateGroup>, path_rules: &PathRules) {
+    for (source, target) in &plan.links {
+        if path_rules.protecting(target).is_some() {
+            continue;
+        }
+        let result = match same_contents(source, target) {
+            Ok(true) => replace_with_link(source, target, plan.kind),
+            Ok(false) => Err(std::io::Error::new(ErrorKind::InvalidData, "contents differ")),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => forget(groups, target),
+            Err(e) => eprintln!("Error linking {:?} to {:?}: {:?}", target, source, e),
+        }
+    }
+    groups.retain(|group| group.paths.len() > 1);
+}
+
+//drops a copy that is gone or linked from the groups
+fn forget(groups: &mut [DuplicateGroup], copy: &Path) {
+    for group in groups.iter_mut() {
+        if let Some(i) = group.paths.iter().position(|path| path == copy) {
+            group.paths.remove(i);
+            group.keep.remove(i);
+        }
+    }
+}
+
+//what moving the unkept copies to trash would do, each paired with the kept copy it duplicates
+#[derive(Clone)]
+pub struct TrashPlan {
+    pub copies: Vec<(PathBuf, PathBuf)>,
+    pub bytes: u64,
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+//a group where nothing is marked to keep is left alone, so the last copy can never be trashed by accident
+pub fn plan_trash(groups: &[DuplicateGroup], path_rules: &PathRules) -> TrashPlan {
+    let mut plan = TrashPlan { copies: Vec::new(), bytes: 0, skipped: Vec::new() };
+    for group in groups {
+        let Some(source) = group.paths.iter().zip(&group.keep).find(|(_, keep)| **keep).map(|(path, _)| path) else {
+            continue;
+        };
+        for (path, keep) in group.paths.iter().zip(&group.keep) {
+            if *keep {
+                continue;
+            }
+            if let Some(protected) = path_rules.protecting(path) {
+                plan.skipped.push((path.clone(), format!("protected by {}", protected)));
+                continue;
+            }
+            plan.copies.push((source.clone(), path.clone()));
+            plan.bytes += group.size;
+        }
+    }
+    plan
+}
+
+//like linking, each copy is compared byte for byte with the kept one right before it goes
+fn apply_trash(plan: &TrashPlan, groups: &mut Vec<DuplicateGroup>, path_rules: &PathRules) {
+    for (source, copy) in &plan.copies {
+        if path_rules.protecting(copy).is_some() {
+            continue;
+        }
+        let result = match same_contents(source, copy) {
+            Ok(true) => crate::trash::move_to_trash(copy).map(|_| ()),
+            Ok(false) => Err(std::io::Error::new(ErrorKind::InvalidData, "contents differ")),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => forget(groups, copy),
+            Err(e) => eprintln!("Error trashing {:?}: {:?}", copy, e),
+        }
+    }
+    groups.retain(|group| group.paths.len() > 1);
+}
+
+fn same_contents(a: &Path, b: &Path) -> Result<bool, std::io::Error> {
+    if fs::metadata(a)?.len() != fs::metadata(b)?.len() {
+        return Ok(false);
+    }
+    let mut a = BufReader::new(crate::open_without_atime(a)?);
+    let mut b = BufReader::new(crate::open_without_atime(b)?);
+    let mut buffer_a = vec![0; 64 * 1024];
+    let mut buffer_b = vec![0; 64 * 1024];
+    loop {
+        let read = a.read(&mut buffer_a)?;
+        if read == 0 {
+            return Ok(true);
+        }
+        b.read_exact(&mut buffer_b[..read])?;
+        if buffer_a[..read] != buffer_b[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+//the link is made next to the target first and renamed over it, so the target is never missing
+fn replace_with_link(source: &Path, target: &Path, kind: LinkKind) -> Result<(), std::io::Error> {
+    let file_name = target.file_name().ok_or(std::io::Error::from(ErrorKind::InvalidInput))?.to_string_lossy().to_string();
+    //neither hard_link nor create_new replace an existing file, so a name already taken is skipped
+    let mut counter = 1;
+    let temp = loop {
+        let candidate = if counter == 1 {
+            target.with_file_name(format!(".{}.dedupe", file_name))
+        } else {
+            target.with_file_name(format!(".{}.dedupe.{}", file_name, counter))
+        };
+        let created = match kind {
+            LinkKind::HardLink => fs::hard_link(source, &candidate),
+            LinkKind::Reflink => OpenOptions::new().write(true).create_new(true).open(&candidate).map(|_| ()),
+        };
+        match created {
+            Ok(()) => break candidate,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => counter += 1,
+            Err(e) => return Err(e),
+        }
+    };
+    //the temp is ours from here on, so it is the only thing removed when something fails
+    let result = match kind {
+        LinkKind::HardLink => Ok(()),
+        LinkKind::Reflink => reflink(source, &temp, target),
+    };
+    if let Err(e) = result.and_then(|_| fs::rename(&temp, target)) {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+    Ok(())
+}
+
+fn reflink(source: &Path, temp: &Path, target: &Path) -> Result<(), std::io::Error> {
+    let source_file = File::open(source)?;
+    let temp_file = OpenOptions::new().write(true).open(temp)?;
+    let result = unsafe { libc::ioctl(temp_file.as_raw_fd(), libc::FICLONE as _, source_file.as_raw_fd()) };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    //unlike a hard link the clone is its own inode, so it keeps the replaced file's permissions
+    fs::set_permissions(temp, fs::metadata(target)?.permissions())
+}
+
+//groups files by size, then by a hash of their first bytes, then by a hash of everything;
+//progress(stage, done, total) is called along the way
+pub fn find_duplicates(root: &Path, mut progress: impl FnMut(&str, usize, usize)) -> Vec<DuplicateGroup> {
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    //hard links to one inode don't take extra space, so only the first path of each is kept
+    let mut seen_inodes = HashSet::new();
+    let mut files = 0;
+    for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
+        let Ok(metadata) = entry.metadata() else { continue };
+        if !metadata.is_file() || metadata.len() == 0 {
+            continue;
+        }
+        if seen_inodes.insert((metadata.dev(), metadata.ino())) {
+            by_size.entry(metadata.len()).or_default().push(entry.into_path());
+            files += 1;
+            if files % 1000 == 0 {
+                progress("Grouping by size", files, 0);
+            }
+        }
+    }
+
+    let candidates: Vec<(u64, Vec<PathBuf>)> = by_size.into_iter().filter(|(_, paths)| paths.len() > 1).collect();
+    let partial = regroup(candidates, "Hashing file starts", Some(PARTIAL_HASH_BYTES), &mut progress);
+    let full = regroup(partial, "Hashing whole files", None, &mut progress);
+
+    let mut groups: Vec<DuplicateGroup> = full
+        .into_iter()
+        .map(|(size, mut paths)| {
+            paths.sort();
+            DuplicateGroup::new(size, paths)
+        })
+        .collect();
+    groups.sort_by_key(|group| std::cmp::Reverse(group.wasted()));
+    groups
+}
+
+//splits every group further by hash, dropping anything left without a twin
+fn regroup(
+    groups: Vec<(u64, Vec<PathBuf>)>,
+    stage: &str,
+    limit: Option<usize>,
+    progress: &mut impl FnMut(&str, usize, usize),
+) -> Vec<(u64, Vec<PathBuf>)> {
+    let total = groups.iter().map(|(_, paths)| paths.len()).sum();
+    let mut done = 0;
+    let mut result = Vec::new();
+    for (size, paths) in groups {
+        let mut by_hash: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+        for path in paths {
+            match hash_file(&path, limit) {
+                Ok(hash) => by_hash.entry(hash).or_default().push(path),
+                Err(e) => eprintln!("Error hashing {:?}: {:?}", path, e),
+            }
+            done += 1;
+            progress(stage, done, total);
+        }
+        result.extend(by_hash.into_values().filter(|paths| paths.len() > 1).map(|paths| (size, paths)));
+    }
+    result
+}
+
+fn hash_file(path: &Path, limit: Option<usize>) -> Result<u64, std::io::Error> {
+    let mut file = crate::open_without_atime(path)?;
+    let mut hasher = DefaultHasher::new();
+    let mut buffer = vec![0; 64 * 1024];
+    let mut remaining = limit.unwrap_or(usize::MAX);
+    while remaining > 0 {
+        let wanted = remaining.min(buffer.len());
+        let read = file.read(&mut buffer[..wanted])?;
+        if read == 0 {
+            break;
+        }
+        hasher.write(&buffer[..read]);
+        remaining -= read;
+    }
+    Ok(hasher.finish())
+}
+
+//runs find_duplicates on its own thread so the UI keeps drawing, repainting as progress comes in
+pub fn spawn(root: PathBuf, scan: Arc<Mutex<DuplicateScan>>, ctx: egui::Context) {
+    {
+        let mut state = scan.lock().unwrap();
+        *state = DuplicateScan { running: true, ..Default::default() };
+    }
+    std::thread::spawn(move || {
+        let groups = find_duplicates(&root, |stage, done, total| {
+            let mut state = scan.lock().unwrap();
+            state.stage = stage.to_string();
+            state.done = done;
+            state.total = total;
+            ctx.request_repaint();
+        });
+        let mut state = scan.lock().unwrap();
+        state.groups = groups;
+        state.running = false;
+        ctx.request_repaint();
+    });
+}
+
+//the review panel: progress while running, then every group with keep checkboxes
+pub fn show(ui: &mut egui::Ui, scan: &Arc<Mutex<DuplicateScan>>, root: &str, path_rules: &PathRules) {
+    //the finder thread locks the scan for every file, so while it runs only the progress is copied out
+    let progress = {
+        let state = scan.lock().unwrap();
+        state.running.then(|| (state.stage.clone(), state.done, state.total))
+    };
+    if let Some((stage, done, total)) = progress {
+        ui.horizontal(|ui| {
+            ui.add_enabled(false, egui::Button::new("Find duplicates"));
+            ui.label(format!("in {}", root));
+        });
+        if total > 0 {
+            ui.add(egui::ProgressBar::new(done as f32 / total as f32).text(format!("{}: {} / {}", stage, done, total)));
+        } else {
+            ui.label(format!("{}: {} files", stage, done));
+        }
+        return;
+    }
+    //once it is done nothing else locks it, so the results are drawn in place
+    let start = show_state(ui, &mut scan.lock().unwrap(), root, path_rules);
+    if start {
+        spawn(PathBuf::from(root), scan.clone(), ui.ctx().clone());
+    }
+}
+
+//returns true when a new search was asked for
+fn show_state(ui: &mut egui::Ui, state: &mut DuplicateScan, root: &str, path_rules: &PathRules) -> bool {
+    let mut start = false;
+    ui.horizontal(|ui| {
+        start = ui.button("Find duplicates").clicked();
+        ui.label(format!("in {}", root));
+    });
+
+    let wasted: u64 = state.groups.iter().map(|group| group.wasted()).sum();
+    ui.label(format!("{} groups, {} wasted", state.groups.len(), crate::format_size(wasted as f64)));
+    ui.horizontal(|ui| {
+        if ui.button("Move unkept copies to trash").clicked() {
+            state.trash_plan = Some(plan_trash(&state.groups, path_rules));
+        }
+        if ui.button("Hard link unkept copies").clicked() {
+            state.link_plan = Some(plan_links(&state.groups, LinkKind::HardLink, path_rules));
+        }
+        if ui.button("Reflink unkept copies").clicked() {
+            state.link_plan = Some(plan_links(&state.groups, LinkKind::Reflink, path_rules));
+        }
+    });
+    if let Some(plan) = state.link_plan.clone() {
+        ui.group(|ui| {
+            ui.label(format!(
+                "Dry run: {} files would be replaced with {}, reclaiming {}",
+                plan.links.len(),
+                plan.kind.describe(),
+                crate::format_size(plan.bytes as f64)
+            ));
+            for (path, reason) in &plan.skipped {
+                ui.label(format!("Skipped {}: {}", path.to_string_lossy(), reason));
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Apply").clicked() {
+                    apply_links(&plan, &mut state.groups, path_rules);
+                    state.link_plan = None;
+                }
+                if ui.button("Cancel").clicked() {
+                    state.link_plan = None;
+                }
+            });
+        });
+    }
+    if let Some(plan) = state.trash_plan.clone() {
+        ui.group(|ui| {
+            ui.label(format!("Move {} copies to trash, reclaiming {}?", plan.copies.len(), crate::format_size(plan.bytes as f64)));
+            for (path, reason) in &plan.skipped {
+                ui.label(format!("Skipped {}: {}", path.to_string_lossy(), reason));
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Move to trash").clicked() {
+                    apply_trash(&plan, &mut state.groups, path_rules);
+                    state.trash_plan = None;
+                }
+                if ui.button("Cancel").clicked() {
+                    state.trash_plan = None;
+                }
+            });
+        });
+    }
+    ui.separator();
+    egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+        for (i, group) in state.groups.iter_mut().enumerate() {
+            let title = format!(
+                "{} x {} - {} wasted",
+                group.paths.len(),
+                crate::format_size(group.size as f64),
+                crate::format_size(group.wasted() as f64)
+            );
+            egui::CollapsingHeader::new(title).id_source(i).show(ui, |ui| {
+                for (path, keep) in group.paths.iter().zip(group.keep.iter_mut()) {
+                    //a protected copy is never removed, so it is shown as kept
+                    match path_rules.protecting(path) {
+                        Some(protected) => {
+                            *keep = true;
+                            ui.add_enabled(false, egui::Checkbox::new(keep, path.to_string_lossy()))
+                                .on_disabled_hover_text(protected_hint(protected));
+                        }
+                        None => {
+                            ui.checkbox(keep, path.to_string_lossy());
+                        }
+                    }
+                }
+            });
+        }
+    });
+    start
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //a fresh directory under the system temp dir for one test
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("duplicate_finder_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn no_rules() -> PathRules {
+        PathRules { excludes: Vec::new(), protected: Vec::new() }
+    }
+
+    #[test]
+    fn same_contents_compares_every_byte() {
+        let dir = scratch("same_contents");
+        let big = vec![7u8; 200 * 1024];
+        let mut changed = big.clone();
+        changed[150 * 1024] = 8;
+        fs::write(dir.join("a"), &big).unwrap();
+        fs::write(dir.join("b"), &big).unwrap();
+        fs::write(dir.join("c"), &changed).unwrap();
+        fs::write(dir.join("d"), &big[1..]).unwrap();
+        assert!(same_contents(&dir.join("a"), &dir.join("b")).unwrap());
+        assert!(!same_contents(&dir.join("a"), &dir.join("c")).unwrap());
+        assert!(!same_contents(&dir.join("a"), &dir.join("d")).unwrap());
+        assert!(same_contents(&dir.join("a"), &dir.join("missing")).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn plan_links_links_unkept_copies_to_the_first_kept_one() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = scratch("plan_links");
+        for name in ["a", "b", "c", "d"] {
+            fs::write(dir.join(name), "same").unwrap();
+        }
+        for name in ["a", "b", "c"] {
+            fs::set_permissions(dir.join(name), fs::Permissions::from_mode(0o644)).unwrap();
+        }
+        fs::set_permissions(dir.join("d"), fs::Permissions::from_mode(0o600)).unwrap();
+        let mut group = DuplicateGroup::new(4, ["a", "b", "c", "d"].iter().map(|name| dir.join(name)).collect());
+        group.keep = vec![false, true, false, false];
+        let mut rules = no_rules();
+        rules.protected.push(dir.join("c").to_string_lossy().to_string());
+
+        let plan = plan_links(&[group.clone()], LinkKind::HardLink, &rules);
+        let links: Vec<(PathBuf, PathBuf)> = vec![(dir.join("b"), dir.join("a"))];
+        assert_eq!(plan.links, links);
+        assert_eq!(plan.bytes, 4);
+        let skipped: Vec<&PathBuf> = plan.skipped.iter().map(|(path, _)| path).collect();
+        assert_eq!(skipped, vec![&dir.join("c"), &dir.join("d")]);
+
+        //nothing kept means nothing to link to
+        group.keep = vec![false; 4];
+        assert!(plan_links(&[group], LinkKind::HardLink, &rules).links.is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
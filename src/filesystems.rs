@@ -0,0 +1,97 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use crate::mounts::{self, FsUsage, Mount};
+
+//a mounted filesystem with its statvfs numbers
+pub struct Filesystem {
+    pub mount: Mount,
+    pub usage: FsUsage,
+}
+
+//mounted filesystems in mount order; pseudo filesystems and empty ones only when all is set
+pub fn list(all: bool) -> Vec<Filesystem> {
+    let mut filesystems: Vec<Filesystem> = Vec::new();
+    for mount in mounts::mounts() {
+        if !all && mounts::is_pseudo(&mount.fs_type) {
+            continue;
+        }
+        let Ok(usage) = mounts::usage(&mount.mount_point) else { continue };
+        if !all && usage.total == 0 {
+            continue;
+        }
+        //something mounted over an earlier mount point hides it
+        filesystems.retain(|filesystem| filesystem.mount.mount_point != mount.mount_point);
+        filesystems.push(Filesystem { mount, usage });
+    }
+    filesystems
+}
+
+//the same table df -h -T printed into the report
+pub fn write_report(path: &Path) -> Result<(), std::io::Error> {
+    let mut file = File::create(path)?;
+    writeln!(file, "{:<30} {:<10} {:>10} {:>10} {:>10} {:>5} {:>12} {:>5} Mounted on", "Filesystem", "Type", "Size", "Used", "Avail", "Use%", "Inodes", "IUse%")?;
+    for filesystem in list(false) {
+        let usage = &filesystem.usage;
+        writeln!(
+            file,
+            "{:<30} {:<10} {:>10} {:>10} {:>10} {:>4}% {:>12} {:>4}% {}",
+            filesystem.mount.source,
+            filesystem.mount.fs_type,
+            crate::format_size(usage.total as f64),
+            crate::format_size(usage.used() as f64),
+            crate::format_size(usage.available as f64),
+            (usage.used_fraction() * 100.0).round(),
+            usage.inodes,
+            (usage.inodes_used_fraction() * 100.0).round(),
+            filesystem.mount.mount_point.display()
+        )?;
+    }
+    Ok(())
+}
+
+//panel listing the filesystems, returns the mount point to scan when one is clicked
+pub fn show(ui: &mut egui::Ui, filesystems: &mut Vec<Filesystem>, all: &mut bool) -> Option<PathBuf> {
+    let mut scan = None;
+    ui.horizontal(|ui| {
+        if ui.button("Refresh").clicked() {
+            *filesystems = list(*all);
+        }
+        if ui.checkbox(all, "Show pseudo filesystems").changed() {
+            *filesystems = list(*all);
+        }
+    });
+    ui.separator();
+    egui::ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+        egui::Grid::new("filesystems").striped(true).show(ui, |ui| {
+            for heading in ["Mount", "Device", "Type", "Size", "Used", "Free", "Use", "Inodes"] {
+                ui.strong(heading);
+            }
+            ui.end_row();
+            for filesystem in filesystems.iter() {
+                let usage = &filesystem.usage;
+                let mount_point = filesystem.mount.mount_point.to_string_lossy();
+                if ui.link(mount_point.as_ref()).on_hover_text("Scan this filesystem").clicked() {
+                    scan = Some(filesystem.mount.mount_point.clone());
+                }
+                ui.label(&filesystem.mount.source).on_hover_text(format!("Device {}", filesystem.mount.device_number));
+                ui.label(&filesystem.mount.fs_type);
+                ui.label(crate::format_size(usage.total as f64));
+                ui.label(crate::format_size(usage.used() as f64));
+                ui.label(crate::format_size(usage.available as f64));
+                ui.add(egui::ProgressBar::new(usage.used_fraction()).desired_width(100.0).show_percentage());
+                if usage.inodes == 0 {
+                    ui.label("-");
+                } else {
+                    ui.add(
+                        egui::ProgressBar::new(usage.inodes_used_fraction())
+                            .desired_width(100.0)
+                            .text(format!("{} / {}", usage.inodes.saturating_sub(usage.inodes_free), usage.inodes)),
+                    );
+                }
+                ui.end_row();
+            }
+        });
+    });
+    scan
+}
@@ -0,0 +1,371 @@
+use std::fs::Metadata;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use serde::{Deserialize, Serialize};
+use crate::owners::OwnerNames;
+
+const MB: u64 = 1024 * 1024;
+const DAY: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EntryKind {
+    Files,
+    Directories,
+}
+
+//one chip of the filter bar; all chips must match for a file to be counted
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum Filter {
+    //only files whose name matches the glob
+    Include(String),
+    //files and whole directories whose name matches the glob are left out
+    Exclude(String),
+    //lowercase, without the dot
+    Extensions(Vec<String>),
+    //bytes, no upper bound when max is None
+    Size { min: u64, max: Option<u64> },
+    //days since the last modification
+    Age { min: u64, max: Option<u64> },
+    Owner(u32),
+    //what the chart shows at the top level; inside directories everything is counted
+    Kind(EntryKind),
+    Compressed,
+}
+
+fn range_label(min: u64, max: Option<u64>, unit: &str) -> String {
+    match max {
+        Some(max) => format!("{}-{} {}", min, max, unit),
+        None => format!("at least {} {}", min, unit),
+    }
+}
+
+impl Filter {
+    pub fn label(&self, names: &OwnerNames) -> String {
+        match self {
+            Filter::Include(glob) => format!("name {}", glob),
+            Filter::Exclude(glob) => format!("not {}", glob),
+            Filter::Extensions(extensions) => format!(".{}", extensions.join(" .")),
+            Filter::Size { min, max } => format!("size {}", range_label(min / MB, max.map(|max| max / MB), "MB")),
+            Filter::Age { min, max } => format!("changed {} ago", range_label(*min, *max, "days")),
+            Filter::Owner(uid) => format!("owner {}", names.user(*uid)),
+            Filter::Kind(EntryKind::Files) => "files only".to_string(),
+            Filter::Kind(EntryKind::Directories) => "directories only".to_string(),
+            Filter::Compressed => "compressed".to_string(),
+        }
+    }
+
+    //whether a file counts, None for filters that don't look at single files
+    fn matches_file(&self, path: &Path, name: &str, metadata: &Metadata) -> Option<bool> {
+        let matches = match self {
+            Filter::Include(glob) => glob_match(glob, name),
+            Filter::Exclude(glob) => !glob_match(glob, name),
+            Filter::Extensions(extensions) => {
+                let extension = name.rsplit_once('.').map(|(_, extension)| extension.to_lowercase()).unwrap_or_default();
+                extensions.contains(&extension)
+            }
+            Filter::Size { min, max } => metadata.len() >= *min && max.map(|max| metadata.len() <= max).unwrap_or(true),
+            Filter::Age { min, max } => {
+                let age = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                    .unwrap_or(Duration::ZERO)
+                    .as_secs()
+                    / DAY;
+                age >= *min && max.map(|max| age <= max).unwrap_or(true)
+            }
+            Filter::Owner(uid) => metadata.uid() == *uid,
+            Filter::Compressed => crate::is_compressed_file(path),
+            Filter::Kind(_) => return None,
+        };
+        Some(matches)
+    }
+}
+
+//* matches any run of characters and ? a single one
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    //where the last * was and how much of the name it has taken so far
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterSet {
+    //entries starting with a dot at the top level of the chart
+    pub show_hidden: bool,
+    pub chips: Vec<Filter>,
+}
+
+impl FilterSet {
+    //top-level entries of the scanned directory that get a sector at all
+    pub fn keeps_entry(&self, name: &str, is_dir: bool) -> bool {
+        if name.starts_with('.') && !self.show_hidden {
+            return false;
+        }
+        self.chips.iter().all(|filter| match filter {
+            Filter::Kind(EntryKind::Files) => !is_dir,
+            Filter::Kind(EntryKind::Directories) => is_dir,
+            Filter::Exclude(glob) => !glob_match(glob, name),
+            _ => true,
+        })
+    }
+
+    //directories are walked unless an exclude chip matches them
+    pub fn skips_dir(&self, name: &str) -> bool {
+        self.chips.iter().any(|filter| matches!(filter, Filter::Exclude(glob) if glob_match(glob, name)))
+    }
+
+    //whether a file's size counts; with_owner false ignores the owner chip, for the per-owner table
+    pub fn counts_file(&self, path: &Path, metadata: &Metadata, with_owner: bool) -> bool {
+        let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        self.chips
+            .iter()
+            .filter(|filter| with_owner || !matches!(filter, Filter::Owner(_)))
+            .all(|filter| filter.matches_file(path, &name, metadata).unwrap_or(true))
+    }
+
+    pub fn owner(&self) -> Option<u32> {
+        self.chips.iter().find_map(|filter| match filter {
+            Filter::Owner(uid) => Some(*uid),
+            _ => None,
+        })
+    }
+
+    pub fn set_owner(&mut self, uid: Option<u32>) {
+        self.chips.retain(|filter| !matches!(filter, Filter::Owner(_)));
+        if let Some(uid) = uid {
+            self.chips.push(Filter::Owner(uid));
+        }
+    }
+
+    pub fn has(&self, filter: &Filter) -> bool {
+        self.chips.contains(filter)
+    }
+
+    pub fn toggle(&mut self, filter: Filter, on: bool) {
+        self.chips.retain(|chip| chip != &filter);
+        if on {
+            self.chips.push(filter);
+        }
+    }
+
+    pub fn describe(&self, names: &OwnerNames) -> String {
+        let mut parts: Vec<String> = self.chips.iter().map(|filter| filter.label(names)).collect();
+        if self.show_hidden {
+            parts.push("hidden entries".to_string());
+        }
+        if parts.is_empty() { "none".to_string() } else { parts.join(", ") }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum NewFilter {
+    Include,
+    Exclude,
+    Extensions,
+    Size,
+    Age,
+    Files,
+    Directories,
+}
+
+//state of the "Add filter" menu
+pub struct FilterEditor {
+    kind: NewFilter,
+    text: String,
+    min: u64,
+    max: u64,
+}
+
+impl Default for FilterEditor {
+    fn default() -> Self {
+        Self { kind: NewFilter::Include, text: String::new(), min: 0, max: 0 }
+    }
+}
+
+impl FilterEditor {
+    fn build(&self) -> Option<Filter> {
+        let text = self.text.trim();
+        //0 as the upper bound means no upper bound
+        let max = (self.max > 0).then_some(self.max);
+        match self.kind {
+            NewFilter::Include if !text.is_empty() => Some(Filter::Include(text.to_string())),
+            NewFilter::Exclude if !text.is_empty() => Some(Filter::Exclude(text.to_string())),
+            NewFilter::Extensions => {
+                let extensions: Vec<String> = text
+                    .split([',', ' '])
+                    .map(|extension| extension.trim().trim_start_matches('.').to_lowercase())
+                    .filter(|extension| !extension.is_empty())
+                    .collect();
+                (!extensions.is_empty()).then_some(Filter::Extensions(extensions))
+            }
+            NewFilter::Size => Some(Filter::Size { min: self.min * MB, max: max.map(|max| max * MB) }),
+            NewFilter::Age => Some(Filter::Age { min: self.min, max }),
+            NewFilter::Files => Some(Filter::Kind(EntryKind::Files)),
+            NewFilter::Directories => Some(Filter::Kind(EntryKind::Directories)),
+            _ => None,
+        }
+    }
+}
+
+//the chips with a remove button each and the "Add filter" menu; returns true when the filters changed
+pub fn show(ui: &mut egui::Ui, filters: &mut FilterSet, editor: &mut FilterEditor, names: &OwnerNames) -> bool {
+    let mut changed = false;
+    ui.horizontal_wrapped(|ui| {
+        let mut removed = None;
+        for (index, filter) in filters.chips.iter().enumerate() {
+            if ui.button(format!("{}  x", filter.label(names))).on_hover_text("Remove this filter").clicked() {
+                removed = Some(index);
+            }
+        }
+        if let Some(index) = removed {
+            filters.chips.remove(index);
+            changed = true;
+        }
+        ui.menu_button("Add filter", |ui| {
+            egui::ComboBox::from_id_source("new_filter")
+                .selected_text(match editor.kind {
+                    NewFilter::Include => "Name matches",
+                    NewFilter::Exclude => "Name doesn't match",
+                    NewFilter::Extensions => "Extensions",
+                    NewFilter::Size => "Size",
+                    NewFilter::Age => "Last changed",
+                    NewFilter::Files => "Files only",
+                    NewFilter::Directories => "Directories only",
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut editor.kind, NewFilter::Include, "Name matches");
+                    ui.selectable_value(&mut editor.kind, NewFilter::Exclude, "Name doesn't match");
+                    ui.selectable_value(&mut editor.kind, NewFilter::Extensions, "Extensions");
+                    ui.selectable_value(&mut editor.kind, NewFilter::Size, "Size");
+                    ui.selectable_value(&mut editor.kind, NewFilter::Age, "Last changed");
+                    ui.selectable_value(&mut editor.kind, NewFilter::Files, "Files only");
+                    ui.selectable_value(&mut editor.kind, NewFilter::Directories, "Directories only");
+                });
+            match editor.kind {
+                NewFilter::Include | NewFilter::Exclude => {
+                    ui.text_edit_singleline(&mut editor.text).on_hover_text("* matches anything, ? one character");
+                }
+                NewFilter::Extensions => {
+                    ui.text_edit_singleline(&mut editor.text).on_hover_text("Comma separated, like mp4, mkv");
+                }
+                NewFilter::Size | NewFilter::Age => {
+                    let unit = if editor.kind == NewFilter::Size { "MB" } else { "days ago" };
+                    ui.horizontal(|ui| {
+                        ui.label("From");
+                        ui.add(egui::DragValue::new(&mut editor.min));
+                        ui.label("to");
+                        ui.add(egui::DragValue::new(&mut editor.max));
+                        ui.label(unit);
+                    });
+                    ui.label("0 as the upper bound means no limit");
+                }
+                NewFilter::Files | NewFilter::Directories => {}
+            }
+            if ui.button("Add").clicked() {
+                if let Some(filter) = editor.build() {
+                    if !filters.has(&filter) {
+                        filters.chips.push(filter);
+                        changed = true;
+                    }
+                    editor.text.clear();
+                }
+                ui.close_menu();
+            }
+        });
+    });
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
+    use super::{glob_match, EntryKind, Filter, FilterSet};
+
+    fn filters(chips: Vec<Filter>) -> FilterSet {
+        FilterSet { show_hidden: false, chips }
+    }
+
+    #[test]
+    fn glob_matches_literals_and_wildcards() {
+        assert!(glob_match("target", "target"));
+        assert!(!glob_match("target", "targets"));
+        assert!(glob_match("*.log", "app.log"));
+        assert!(glob_match("*.log", ".log"));
+        assert!(!glob_match("*.log", "app.log.1"));
+        assert!(glob_match("file?.txt", "file1.txt"));
+        assert!(!glob_match("file?.txt", "file.txt"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("?", ""));
+    }
+
+    #[test]
+    fn glob_star_backtracks() {
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(glob_match("*-lock", ".X0-lock-lock"));
+        assert!(glob_match(".X*-lock", ".X0-lock"));
+        assert!(!glob_match("a*b*c", "aXbYbZ"));
+        assert!(glob_match("**x", "abx"));
+    }
+
+    #[test]
+    fn top_level_entries_follow_hidden_kind_and_exclude_chips() {
+        let set = filters(vec![]);
+        assert!(set.keeps_entry("src", true));
+        assert!(!set.keeps_entry(".git", true));
+        assert!(FilterSet { show_hidden: true, chips: vec![] }.keeps_entry(".git", true));
+
+        let set = filters(vec![Filter::Kind(EntryKind::Directories), Filter::Exclude("target".to_string())]);
+        assert!(set.keeps_entry("src", true));
+        assert!(!set.keeps_entry("Cargo.toml", false));
+        assert!(!set.keeps_entry("target", true));
+        //only files are matched against include chips, directories stay to be walked
+        assert!(filters(vec![Filter::Include("*.rs".to_string())]).keeps_entry("src", true));
+    }
+
+    #[test]
+    fn files_count_when_every_chip_matches() {
+        let dir = std::env::temp_dir().join(format!("crate-filters-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notes.TXT");
+        fs::write(&path, vec![0u8; 2048]).unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+
+        assert!(filters(vec![]).counts_file(&path, &metadata, true));
+        assert!(filters(vec![Filter::Include("notes.*".to_string())]).counts_file(&path, &metadata, true));
+        assert!(!filters(vec![Filter::Exclude("*.TXT".to_string())]).counts_file(&path, &metadata, true));
+        assert!(filters(vec![Filter::Extensions(vec!["txt".to_string()])]).counts_file(&path, &metadata, true));
+        assert!(filters(vec![Filter::Size { min: 1024, max: Some(4096) }]).counts_file(&path, &metadata, true));
+        assert!(!filters(vec![Filter::Size { min: 4096, max: None }]).counts_file(&path, &metadata, true));
+        assert!(filters(vec![Filter::Age { min: 0, max: Some(1) }]).counts_file(&path, &metadata, true));
+        assert!(!filters(vec![Filter::Age { min: 1, max: None }]).counts_file(&path, &metadata, true));
+        assert!(filters(vec![Filter::Kind(EntryKind::Directories)]).counts_file(&path, &metadata, true));
+
+        //the owner chip is skipped when the caller sums every owner
+        let other_owner = filters(vec![Filter::Owner(metadata.uid().wrapping_add(1))]);
+        assert!(!other_owner.counts_file(&path, &metadata, true));
+        assert!(other_owner.counts_file(&path, &metadata, false));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use egui::plot::{Line, Plot, PlotPoints};
+use sysinfo::{PidExt, ProcessExt, ProcessRefreshKind, System, SystemExt};
+
+const INTERVAL: Duration = Duration::from_secs(1);
+//one minute of samples
+const HISTORY: usize = 60;
+const SHOWN: usize = 20;
+
+//read and write rates of one process, newest sample last
+struct ProcessIo {
+    name: String,
+    cmdline: String,
+    read: VecDeque<f64>,
+    write: VecDeque<f64>,
+}
+
+impl ProcessIo {
+    fn current(&self) -> (f64, f64) {
+        (self.read.back().copied().unwrap_or(0.0), self.write.back().copied().unwrap_or(0.0))
+    }
+}
+
+//bytes read and written per second by each process, sampled from /proc/<pid>/io through sysinfo;
+//only our own processes are readable unless we run as root
+pub struct IoMonitor {
+    system: System,
+    last_sample: Option<Instant>,
+    processes: HashMap<u32, ProcessIo>,
+}
+
+impl Default for IoMonitor {
+    fn default() -> Self {
+        Self { system: System::new(), last_sample: None, processes: HashMap::new() }
+    }
+}
+
+impl IoMonitor {
+    fn sample(&mut self) {
+        let now = Instant::now();
+        let elapsed = match self.last_sample {
+            Some(last) if now - last < INTERVAL => return,
+            Some(last) => (now - last).as_secs_f64(),
+            None => 0.0,
+        };
+        self.last_sample = Some(now);
+        self.system.refresh_processes_specifics(ProcessRefreshKind::new().with_disk_usage());
+
+        let mut seen = Vec::new();
+        for process in self.system.processes().values() {
+            let pid = process.pid().as_u32();
+            seen.push(pid);
+            let usage = process.disk_usage();
+            match self.processes.get_mut(&pid) {
+                Some(io) if elapsed > 0.0 => {
+                    io.read.push_back(usage.read_bytes as f64 / elapsed);
+                    io.write.push_back(usage.written_bytes as f64 / elapsed);
+                    if io.read.len() > HISTORY {
+                        io.read.pop_front();
+                        io.write.pop_front();
+                    }
+                }
+                Some(_) => {}
+                //the first reading of a process is its total since it started, not a rate
+                None => {
+                    self.processes.insert(pid, ProcessIo {
+                        name: process.name().to_string(),
+                        cmdline: process.cmd().join(" "),
+                        read: VecDeque::new(),
+                        write: VecDeque::new(),
+                    });
+                }
+            }
+        }
+        self.processes.retain(|pid, _| seen.contains(pid));
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        self.sample();
+        ui.ctx().request_repaint_after(INTERVAL);
+
+        let mut busiest: Vec<(&u32, &ProcessIo)> = self.processes.iter().filter(|(_, io)| io.read.iter().chain(&io.write).any(|rate| *rate > 0.0)).collect();
+        busiest.sort_by(|a, b| {
+            let (a, b) = (a.1.current(), b.1.current());
+            (b.0 + b.1).partial_cmp(&(a.0 + a.1)).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let (read, write) = self.processes.values().map(ProcessIo::current).fold((0.0, 0.0), |total, rate| (total.0 + rate.0, total.1 + rate.1));
+        ui.label(format!("Reading {}/s, writing {}/s", crate::format_size(read), crate::format_size(write)));
+        ui.separator();
+        if busiest.is_empty() {
+            ui.label("No disk activity in the last minute.");
+        }
+        egui::Grid::new("io_monitor").striped(true).show(ui, |ui| {
+            for heading in ["Process", "PID", "Read", "Write", "Last minute"] {
+                ui.strong(heading);
+            }
+            ui.end_row();
+            for (pid, io) in busiest.into_iter().take(SHOWN) {
+                let (read, write) = io.current();
+                ui.label(&io.name).on_hover_text(&io.cmdline);
+                ui.label(pid.to_string());
+                ui.label(format!("{}/s", crate::format_size(read)));
+                ui.label(format!("{}/s", crate::format_size(write)));
+                sparkline(ui, *pid, io);
+                ui.end_row();
+            }
+        });
+    }
+}
+
+//tiny plot of the read (blue) and write (red) history
+fn sparkline(ui: &mut egui::Ui, pid: u32, io: &ProcessIo) {
+    let points = |rates: &VecDeque<f64>| PlotPoints::from_iter(rates.iter().enumerate().map(|(i, rate)| [i as f64, *rate]));
+    Plot::new(("io_sparkline", pid))
+        .width(150.0)
+        .height(24.0)
+        .show_axes([false; 2])
+        .show_x(false)
+        .show_y(false)
+        .show_background(false)
+        .allow_boxed_zoom(false)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .include_x(0.0)
+        .include_x(HISTORY as f64)
+        .include_y(0.0)
+        .show(ui, |plot_ui| {
+            plot_ui.line(Line::new(points(&io.read)).color(egui::Color32::LIGHT_BLUE));
+            plot_ui.line(Line::new(points(&io.write)).color(egui::Color32::LIGHT_RED));
+        });
+}
@@ -1,58 +1,103 @@
-use std::time::SystemTime;
-use std::time::Duration;
 use std::f64::consts::TAU;
+use std::time::SystemTime;
 use egui::plot::{Legend, Plot, PlotPoint, PlotPoints, Polygon, Text};
 use egui::{Align2, RichText};
 use eframe::egui;
-use egui::Style;
 use native_dialog::FileDialog;
-use eframe::NativeOptions;
-use egui_extras::{Size, StripBuilder};
 use walkdir::WalkDir;
-use walkdir::DirEntry;
-use std::fs::metadata;
+use std::io::Write;
 use std::fs;
-use egui::Color32;
 use egui::Visuals;
-use egui::Stroke;
+use std::os::unix::fs::MetadataExt;
 use std::path::Path;
 use std::path::PathBuf;
 const FULL_CIRCLE_VERTICES: f64 = 360.0;
-use std::cell::RefCell;
-use std::rc::Rc;
-use duplicates::paths::get_descendants;
-use duplicates::duplicates::get_duplicates_hashed;
-use std::fs::File;
-use std::io::Write;
-use sysinfo::{NetworkExt, NetworksExt, ProcessExt, System, SystemExt};
-//use core::error::Error;
-use core::fmt::Error;
-use std::fs::OpenOptions;
-//use eframe::Error;
-//use native_dialog::Error;
-use std::process::Command;
-fn is_directory_empty<P: AsRef<std::path::Path>>(dir_path: P) -> bool {
-    let walker = WalkDir::new(&dir_path).into_iter();
-
-    // Check if there are no entries in the directory
-    walker.filter_map(|entry| entry.ok()).next().is_none()
+const DEFAULT_WINDOW_SIZE: egui::Vec2 = egui::vec2(10000.0, 10000.0);
+//slices of the whole-disk mode, which stand for space rather than a path
+const FREE_SPACE: &str = "free space";
+const UNACCOUNTED: &str = "unaccounted";
+use std::sync::{Arc, Mutex};
+mod archives;
+mod block_devices;
+mod build_artifacts;
+mod caches;
+mod compressibility;
+mod duplicate_finder;
+mod filesystems;
+mod filters;
+mod io_monitor;
+mod mounts;
+mod open_files;
+mod owners;
+mod path_rules;
+mod recommendations;
+mod rules;
+mod settings;
+mod throughput;
+mod trash;
+use owners::{OwnerNames, OwnerUsage};
+//by content, falling back to the extension when the file can't be read
+fn is_compressed_file(path: &Path) -> bool {
+    archives::detect(path).is_some()
+}
+
+//for reading contents without making the file look recently used to the stale directories rule;
+//O_NOATIME is only allowed on files we own, others are opened normally
+fn open_without_atime(path: &Path) -> std::io::Result<fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NOATIME)
+        .open(path)
+        .or_else(|e| if e.raw_os_error() == Some(libc::EPERM) { fs::File::open(path) } else { Err(e) })
+}
+
+fn format_size(size: f64) -> String {
+    if size >= (1024.0*1024.0*1024.0) {
+        format!("{} GB", size/(1024.0*1024.0*1024.0))
+    } else if size >= (1024.0*1024.0) {
+        format!("{} MB", size/(1024.0*1024.0))
+    } else if size >= 1024.0 {
+        format!("{} KB", size/1024.0)
+    } else {
+        format!("{} B", size)
+    }
 }
-fn is_hidden(entry: &DirEntry) -> bool {
-    entry.file_name()
-         .to_str()
-         .map(|s| s.starts_with("."))
-         .unwrap_or(false)
+
+//bytes of all files below path, without following symlinks
+fn directory_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+//how long ago a time was, in the largest whole unit
+fn format_age(time: SystemTime) -> String {
+    let seconds = SystemTime::now().duration_since(time).map(|age| age.as_secs()).unwrap_or(0);
+    if seconds >= 24 * 60 * 60 {
+        format!("{} days ago", seconds / (24 * 60 * 60))
+    } else if seconds >= 60 * 60 {
+        format!("{} hours ago", seconds / (60 * 60))
+    } else {
+        format!("{} minutes ago", seconds / 60)
+    }
 }
-fn is_compressed_file(entry: &DirEntry) -> bool {
-    let compressed_extensions = [".zip", ".gz", ".tar",".rar"];
-    entry
-        .path()
-        .to_str()
-        .map(|name| compressed_extensions.iter().any(|&ext| name.ends_with(ext)))
-        .unwrap_or(false)
+
+//local calendar date as YYYY-MM-DD
+fn format_date(time: SystemTime) -> String {
+    let seconds = time.duration_since(SystemTime::UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or(0) as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe { libc::localtime_r(&seconds, &mut tm) };
+    format!("{:04}-{:02}-{:02}", tm.tm_year + 1900, tm.tm_mon + 1, tm.tm_mday)
 }
 
-fn calculate_directory_size(directory_path: &str) -> Result<f64, std::io::Error> {
+//only files passing the filters count towards the returned total and the per-owner usage, which ignores the owner filter;
+//excluded paths aren't even walked
+fn calculate_directory_size(directory_path: &str, owners: &mut OwnerUsage, filters: &filters::FilterSet, excludes: &path_rules::PathRules) -> Result<f64, std::io::Error> {
     let path = Path::new(directory_path);
     if path.is_dir() {
         let mut total_size = 0.0;
@@ -61,44 +106,157 @@ fn calculate_directory_size(directory_path: &str) -> Result<f64, std::io::Error>
             let entry = entry?;
             let entry_path: PathBuf = entry.path();
             let entry_path_str = entry_path.to_str().ok_or(std::io::Error::from(std::io::ErrorKind::InvalidData))?;
+            if excludes.is_excluded(&entry_path) {
+                continue;
+            }
 
             if entry_path.is_file() {
-                total_size += fs::metadata(&entry_path)?.len() as f64;
-            } else if entry_path.is_dir() {
-                total_size += calculate_directory_size(entry_path_str)?;
+                total_size += file_size(&entry_path, owners, filters)?;
+            } else if entry_path.is_dir() && !filters.skips_dir(&entry.file_name().to_string_lossy()) {
+                total_size += calculate_directory_size(entry_path_str, owners, filters, excludes)?;
             }
         }
 
         Ok(total_size)
-    }else if path.is_file(){
-    //let entry = entry?;
-            //let entry_path: PathBuf = path;
-    let mut total_size = 0.0;
-      total_size += fs::metadata(&path)?.len() as f64;
-       Ok(total_size)
+    } else if path.is_file() {
+        file_size(path, owners, filters)
     } else {
         Ok(0.0) // Not a directory, return 0.0 size
     }
 }
+
+fn file_size(path: &Path, owners: &mut OwnerUsage, filters: &filters::FilterSet) -> Result<f64, std::io::Error> {
+    let metadata = fs::metadata(path)?;
+    let size = metadata.len() as f64;
+    if !filters.counts_file(path, &metadata, false) {
+        return Ok(0.0);
+    }
+    owners.add(metadata.uid(), metadata.gid(), size);
+    if filters.counts_file(path, &metadata, true) { Ok(size) } else { Ok(0.0) }
+}
+//size, number of items and per-owner usage below a path, shown before removing it
+fn summarize_path(path: &str) -> (f64, usize, OwnerUsage) {
+    let mut owners = OwnerUsage::default();
+    let mut size = 0.0;
+    let mut items = 0;
+    //a symlink is removed as a link, so it is the link that gets counted, not its target
+    for entry in WalkDir::new(path).follow_root_links(false).into_iter().filter_map(|e| e.ok()) {
+        items += 1;
+        if let Ok(metadata) = entry.metadata() {
+            if metadata.is_file() || metadata.file_type().is_symlink() {
+                size += metadata.len() as f64;
+                owners.add(metadata.uid(), metadata.gid(), metadata.len() as f64);
+            }
+        }
+    }
+    (size, items, owners)
+}
+
+//what a click or a context menu entry on a sector or table row asks for
+#[derive(Clone)]
+enum SectorAction {
+    Open(String),
+    Trash(String),
+    Delete(String),
+    Handles(String),
+    Compressibility(String),
+}
+
+fn sector_menu(ui: &mut egui::Ui, path: &str, action: &mut Option<SectorAction>) {
+    if ui.button("Open handles").clicked() {
+        *action = Some(SectorAction::Handles(path.to_string()));
+        ui.close_menu();
+    }
+    if ui.button("Estimate compression").clicked() {
+        *action = Some(SectorAction::Compressibility(path.to_string()));
+        ui.close_menu();
+    }
+    if ui.button("Move to trash").clicked() {
+        *action = Some(SectorAction::Trash(path.to_string()));
+        ui.close_menu();
+    }
+    if ui.button("Delete permanently").clicked() {
+        *action = Some(SectorAction::Delete(path.to_string()));
+        ui.close_menu();
+    }
+}
+
+#[derive(Clone)]
+struct PendingRemoval {
+    path: String,
+    size: f64,
+    items: usize,
+    owners: OwnerUsage,
+    permanent: bool,
+    //the protected entry that forbids removing it
+    protected_by: Option<String>,
+    //processes still using something inside, which deleting won't stop
+    handles: Vec<open_files::Handle>,
+}
+
 #[derive(Clone)]
 struct PieChart {
     name: String,
     sectors: Vec<Sector>,
+    //path of the sector the context menu was opened on
+    context_path: Option<String>,
 }
-#[derive(Clone)]
 struct MyApp {
     path: String,
     scan_clicked: bool, 
     scanning_path: String,
     pie_chart: PieChart,
-    show_pie_chart: bool,
     small_directories: Vec<String>,
     small_directories2: Vec<f64>,
+    //(size, name, path) of every entry of the last scan, so removals can update the chart without rescanning
+    entries: Vec<(f64, String, String)>,
+    pending_removal: Option<PendingRemoval>,
     radius: f64,
-    hidden: bool, 
-    compressed: bool, 
+    filters: filters::FilterSet,
+    filter_editor: filters::FilterEditor,
     sorted: bool,
-    recommendations: bool,
+    recommendations: recommendations::RecommendationsPanel,
+    show_recommendations: bool,
+    owner_usage: OwnerUsage,
+    owner_names: OwnerNames,
+    show_owners: bool,
+    show_trash: bool,
+    trash: trash::TrashPanel,
+    show_duplicates: bool,
+    duplicate_scan: Arc<Mutex<duplicate_finder::DuplicateScan>>,
+    rules: Arc<Mutex<rules::RuleSet>>,
+    show_rules: bool,
+    show_build_artifacts: bool,
+    build_artifacts: build_artifacts::ArtifactsPanel,
+    show_caches: bool,
+    caches: caches::CachesPanel,
+    show_filesystems: bool,
+    filesystems: Vec<filesystems::Filesystem>,
+    all_filesystems: bool,
+    whole_disk: bool,
+    show_deleted_files: bool,
+    deleted_files: open_files::DeletedFilesPanel,
+    //path the open handles window looks at, None while it is closed
+    handles_path: Option<String>,
+    handles: Vec<open_files::Handle>,
+    show_io_monitor: bool,
+    io_monitor: io_monitor::IoMonitor,
+    show_block_devices: bool,
+    block_devices: Vec<block_devices::BlockDevice>,
+    show_throughput: bool,
+    throughput: throughput::ThroughputMonitor,
+    dark_mode: bool,
+    path_rules: path_rules::PathRules,
+    show_path_rules: bool,
+    new_exclude: String,
+    new_protected: String,
+    show_archives: bool,
+    archive_totals: Vec<(archives::Format, usize, u64)>,
+    //the archive being browsed and the children of the directory shown inside it
+    archive: Option<archives::Listing>,
+    archive_children: Vec<archives::ArchiveChild>,
+    show_compressibility: bool,
+    compressibility: Option<compressibility::Estimate>,
 }
 impl PieChart {
     //creates empty pie chart, which will eventually be updated w/ proper radius
@@ -106,6 +264,7 @@ impl PieChart {
         Self {
             name: String::new(),
             sectors: Vec::new(),
+            context_path: None,
         }
     }
     
@@ -137,18 +296,22 @@ impl PieChart {
         Self {
             name: name.as_ref().to_string(),
             sectors,
+            context_path: None,
         }
     }
 
-    pub fn show(&mut self, ui: &mut egui::Ui) -> String{
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<SectorAction> {
         let sectors = self.sectors.clone();
-         let desired_size = egui::vec2(100.0, 100.0); // Set your desired size here
-    //let desired_size_usize = egui::vec2(desired_size.x as usize, desired_size.y as usize);
 
         //copy current context for click checking
         let ctx = ui.ctx().clone();
-        let mut temp_str = String::new();
-        Plot::new(&self.name)
+        let mut action = None;
+        let mut context_path = self.context_path.clone();
+        let secondary = ctx.input(|input| input.pointer.secondary_released());
+        if secondary {
+            context_path = None;
+        }
+        let response = Plot::new(&self.name)
             .width(1290.0)
             .height(530.0)
             .label_formatter(|_: &str, _: &PlotPoint| String::default())
@@ -173,44 +336,34 @@ impl PieChart {
 
                     plot_ui.polygon(Polygon::new(PlotPoints::new(points)).name(&name).highlight(highlight));
                     //check for click, uses closure (aka fxn) to check if mouse was released
-                    if highlight && ctx.input(|input| input.pointer.any_released()) {
-                        temp_str = format!("{}", String::from(sector.path.clone()));
+                    let disk_slice = sector.path == FREE_SPACE || sector.path == UNACCOUNTED;
+                    if highlight && !disk_slice && ctx.input(|input| input.pointer.primary_released()) {
+                        action = Some(SectorAction::Open(sector.path.clone()));
+                    }
+                    if highlight && secondary && sector.path != "others" && !disk_slice {
+                        context_path = Some(sector.path.clone());
                     }
                     if highlight {
                         let p = plot_ui.pointer_coordinate().unwrap();
-                        let mut p1 = p.clone(); 
-                        p1.y = p1.y-0.05;
+                        let mut p1 = p;
+                        p1.y -= 0.05;
                         // TODO proper zoom
                         let text = RichText::new(&name).size(15.0).heading();
-                        let text1 = RichText::new(&(sector.size).to_string()).size(15.0).heading();
                         plot_ui.text(Text::new(p, text).name(&name).anchor(Align2::LEFT_BOTTOM));
-                         if sector.size >= (1024.0*1024.0*1024.0)
-      {
-            let label = format!("{} GB",  sector.size/(1024.0*1024.0*1024.0));
-           let text1 = RichText::new(&(label).to_string()).size(15.0).heading();
-            plot_ui.text(Text::new(p1, text1).name(&(label).to_string()).anchor(Align2::LEFT_BOTTOM));
-     }else if sector.size >= (1024.0*1024.0)
-     {
-         let label = format!("{} MB",  sector.size/(1024.0*1024.0));
-          let text1 = RichText::new(&(label).to_string()).size(15.0).heading();
-           plot_ui.text(Text::new(p1, text1).name(&(label).to_string()).anchor(Align2::LEFT_BOTTOM));
-     }
-     else if sector.size >= (1024.0)
-     {
-         let label = format!(" {} KB", sector.size/1024.0);
-         let text1 = RichText::new(&(label).to_string()).size(15.0).heading();
-          plot_ui.text(Text::new(p1, text1).name(&(label).to_string()).anchor(Align2::LEFT_BOTTOM));
-     }
-     else
-     {
-         let label = format!(" {} B", sector.size);
-          let text1 = RichText::new(&(label).to_string()).size(15.0).heading();
-           plot_ui.text(Text::new(p1, text1).name(&(label).to_string()).anchor(Align2::LEFT_BOTTOM));
-     }
+                        let label = format_size(sector.size);
+                        let text1 = RichText::new(&label).size(15.0).heading();
+                        plot_ui.text(Text::new(p1, text1).name(&label).anchor(Align2::LEFT_BOTTOM));
                     }
                 }
+            }).response;
+        self.context_path = context_path;
+        if let Some(path) = self.context_path.clone() {
+            response.context_menu(|ui| {
+                ui.label(&path);
+                sector_menu(ui, &path, &mut action);
             });
-            temp_str
+        }
+        action
     }
 }
 
@@ -225,6 +378,7 @@ struct Sector {
 }
 
 impl Sector {
+    #[allow(clippy::too_many_arguments)]
     pub fn new<S: AsRef<str>, P: AsRef<str>>(name: S, start: f64, end: f64, vertices: usize, step: f64, path: P, radius: f64, size: f64) -> Self {
         let mut points = vec![];
 
@@ -271,21 +425,65 @@ impl Default for MyApp {
             scan_clicked: true,
             scanning_path: "/home".to_owned(),
             pie_chart: PieChart::new_empty(),
-            show_pie_chart: true,
             // Initialize small_directories as an empty vector
             small_directories: Vec::new(),
             small_directories2: Vec::new(),
+            entries: Vec::new(),
+            pending_removal: None,
             radius: 0.0,
-            hidden: false, 
-            compressed: false, 
+            filters: filters::FilterSet::default(),
+            filter_editor: filters::FilterEditor::default(),
             sorted: false,
-            recommendations : false,
+            recommendations: recommendations::RecommendationsPanel::default(),
+            show_recommendations: false,
+            owner_usage: OwnerUsage::default(),
+            owner_names: OwnerNames::load(),
+            show_owners: false,
+            show_trash: false,
+            trash: trash::TrashPanel::default(),
+            show_duplicates: false,
+            duplicate_scan: Arc::new(Mutex::new(duplicate_finder::DuplicateScan::default())),
+            rules: Arc::new(Mutex::new(rules::RuleSet::with_defaults())),
+            show_rules: false,
+            show_build_artifacts: false,
+            build_artifacts: build_artifacts::ArtifactsPanel::default(),
+            show_caches: false,
+            caches: caches::CachesPanel::default(),
+            show_filesystems: false,
+            filesystems: Vec::new(),
+            all_filesystems: false,
+            whole_disk: false,
+            show_deleted_files: false,
+            deleted_files: open_files::DeletedFilesPanel::default(),
+            handles_path: None,
+            handles: Vec::new(),
+            show_io_monitor: false,
+            io_monitor: io_monitor::IoMonitor::default(),
+            show_block_devices: false,
+            block_devices: Vec::new(),
+            show_throughput: false,
+            throughput: throughput::ThroughputMonitor::default(),
+            dark_mode: true,
+            path_rules: path_rules::PathRules::default(),
+            show_path_rules: false,
+            new_exclude: String::new(),
+            new_protected: String::new(),
+            show_archives: false,
+            archive_totals: Vec::new(),
+            archive: None,
+            archive_children: Vec::new(),
+            show_compressibility: false,
+            compressibility: None,
         }
     }
 }
 
 impl eframe::App for MyApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        settings::save(storage, &self.settings());
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("Disk Analyzer");
             //find max size of x and y axis, radius will be less than that.
@@ -295,17 +493,48 @@ impl eframe::App for MyApp {
                 if ui.button("Scan").clicked() {
                     self.scanning_path = self.path.clone(); 
                     self.scan_clicked = true; 
-                    //self.show_pie_chart = true;
-                    self.update_pie_chart_data(ui);
+                    self.update_pie_chart_data();
                 }
                 let path_label = ui.label("Path: ");
                 ui.text_edit_singleline(&mut self.path)
                     .labelled_by(path_label.id);
                 ui.label("filters");
-                ui.checkbox(&mut self.hidden, "Hidden files");
-                ui.checkbox(&mut self.compressed, "Compressed files");
+                let mut filters_changed = ui.checkbox(&mut self.filters.show_hidden, "Hidden files").changed();
+                let mut compressed = self.filters.has(&filters::Filter::Compressed);
+                if ui.checkbox(&mut compressed, "Compressed files").changed() {
+                    self.filters.toggle(filters::Filter::Compressed, compressed);
+                    filters_changed = true;
+                }
                 ui.checkbox(&mut self.sorted, "Sorted visual");
+                if ui.checkbox(&mut self.whole_disk, "Whole disk").on_hover_text("Show free and unaccounted space when a filesystem root is scanned").changed() {
+                    self.build_pie_chart();
+                }
+                let mut owner = self.filters.owner();
+                let owner_text = match owner {
+                    Some(uid) => self.owner_names.user(uid),
+                    None => "All owners".to_string(),
+                };
+                egui::ComboBox::from_label("Owner")
+                    .selected_text(owner_text)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut owner, None, "All owners");
+                        let mut uids: Vec<u32> = self.owner_usage.by_uid.keys().copied().collect();
+                        uids.sort();
+                        for uid in uids {
+                            ui.selectable_value(&mut owner, Some(uid), self.owner_names.user(uid));
+                        }
+                    });
+                if owner != self.filters.owner() {
+                    self.filters.set_owner(owner);
+                    filters_changed = true;
+                }
+                if filters_changed {
+                    self.update_pie_chart_data();
+                }
             });
+            if filters::show(ui, &mut self.filters, &mut self.filter_editor, &self.owner_names) {
+                self.update_pie_chart_data();
+            }
             ui.horizontal(|ui| {
             if ui.button("Browse").clicked() {
                 // Open a folder selection dialog using new_picker()
@@ -316,7 +545,7 @@ impl eframe::App for MyApp {
                     self.path = folder.expect("failed").to_str().unwrap_or_default().to_owned();
                     self.scanning_path = self.path.clone();
                 }
-                self.update_pie_chart_data(ui);
+                self.update_pie_chart_data();
             }
             if ui.button("Up").clicked() {
                 if self.path.is_empty() || self.path == "/home" {
@@ -326,7 +555,7 @@ impl eframe::App for MyApp {
                     self.path = self.path.clone().chars().take(index.unwrap_or(self.path.clone().len())).collect();     
                 }
                 self.scanning_path = self.path.clone();
-                self.update_pie_chart_data(ui);
+                self.update_pie_chart_data();
             }
             if ui.button("Report").clicked() {
                 if let Err(e) = self.create_file() {
@@ -336,166 +565,355 @@ impl eframe::App for MyApp {
                 }
             }
             if ui.button("Recommendations").clicked() {
-                if let Err(e) = self.recommendations() {
-                    eprintln!("Error creating file: {:?}", e);
-                } else {
-                   self.recommendations = true;
-                }
-                
+                self.recommendations(ui.ctx());
+            }
+            ui.checkbox(&mut self.show_owners, "Owners");
+            if ui.checkbox(&mut self.show_filesystems, "Filesystems").changed() && self.show_filesystems {
+                self.filesystems = filesystems::list(self.all_filesystems);
+            }
+            if ui.checkbox(&mut self.show_block_devices, "Devices").changed() && self.show_block_devices {
+                self.block_devices = block_devices::layout();
+            }
+            if ui.checkbox(&mut self.show_deleted_files, "Deleted open files").changed() && self.show_deleted_files {
+                self.deleted_files.refresh();
+            }
+            if ui.checkbox(&mut self.show_io_monitor, "Disk I/O").changed() && self.show_io_monitor {
+                self.io_monitor = io_monitor::IoMonitor::default();
+            }
+            if ui.checkbox(&mut self.show_throughput, "Throughput").changed() && self.show_throughput {
+                self.throughput.reset();
+            }
+            ui.checkbox(&mut self.show_duplicates, "Duplicates");
+            ui.checkbox(&mut self.show_build_artifacts, "Build artifacts");
+            if ui.checkbox(&mut self.show_caches, "Caches").changed() && self.show_caches {
+                self.caches.entries = caches::find_caches();
+            }
+            if ui.checkbox(&mut self.show_archives, "Archives").changed() && self.show_archives {
+                self.archive_totals = archives::totals(Path::new(&self.scanning_path), &self.path_rules);
+            }
+            if ui.checkbox(&mut self.show_compressibility, "Compression").changed() && self.show_compressibility {
+                self.compressibility = Some(compressibility::estimate(Path::new(&self.scanning_path), &self.path_rules));
+            }
+            ui.checkbox(&mut self.show_rules, "Rules");
+            ui.checkbox(&mut self.show_path_rules, "Excludes");
+            if ui.checkbox(&mut self.show_trash, "Trash").changed() && self.show_trash {
+                self.trash.refresh();
             }
             if ui.button("Toggle Dark/Light Mode").clicked() {
-                let visuals = if ui.visuals().dark_mode {
-                    Visuals::light()
-                } else {
-                    Visuals::dark()
-                };
-                ctx.set_visuals(visuals);
+                self.dark_mode = !self.dark_mode;
+                ctx.set_visuals(if self.dark_mode { Visuals::dark() } else { Visuals::light() });
             }
-            if self.recommendations {
-                ui.label("Recommendations generated!");
+            if ui.button("Reset settings").on_hover_text("Restore the default path, filters, theme and window layout").clicked() {
+                //excludes and protected paths are kept, losing them could be costly
+                let defaults = settings::Settings { path_rules: self.path_rules.clone(), ..Default::default() };
+                self.apply_settings(defaults, ctx);
+                //forgets where the panels were placed
+                ctx.memory_mut(|memory| *memory = Default::default());
+                frame.set_window_size(DEFAULT_WINDOW_SIZE);
             }
              });
+            let mut action = None;
             if self.scan_clicked {
-                let temp_str = self.pie_chart.show(ui);
-                if temp_str != "" {
-                    self.path = temp_str; 
-                    self.scanning_path = self.path.clone();
-                    self.update_pie_chart_data(ui);
-                }
+                action = self.pie_chart.show(ui);
             }
              let row_height = 10.0;
 let total_rows = 10;
-   egui::ScrollArea::vertical().max_height(20.0).max_width(200.0).auto_shrink([false;2]).show_rows(ui, row_height, total_rows, |ui, row_range| {
-   if !self.small_directories.is_empty(){let mut combined_labels: Vec<String> = Vec::new();
-
+   egui::ScrollArea::vertical().max_height(20.0).max_width(200.0).auto_shrink([false;2]).show_rows(ui, row_height, total_rows, |ui, _row_range| {
+   if !self.small_directories.is_empty(){
     // Combine the labels from small_directories and converted f64 values from small_directories2
-    for (directory, num) in self.small_directories.iter().zip(&self.small_directories2) {if num >= &(1024.0*1024.0*1024.0)
-    {
-        let label = format!("{} - {} GB", directory, num/(1024.0*1024.0*1024.0));
-        combined_labels.push(label);
-    }else if num >= &(1024.0*1024.0)
-    {
-        let label = format!("{} - {} MB", directory, num/(1024.0*1024.0));
-        combined_labels.push(label);
+    for (directory, num) in self.small_directories.iter().zip(&self.small_directories2) {
+        let label = format!("{} - {}", directory, format_size(*num));
+        let directory_path = Path::new(&self.scanning_path).join(directory).to_string_lossy().to_string();
+        ui.add(egui::Label::new(label).sense(egui::Sense::click()))
+            .context_menu(|ui| sector_menu(ui, &directory_path, &mut action));
     }
-    else if num >= &(1024.0)
-    {
-        let label = format!("{} - {} KB", directory, num/1024.0);
-        combined_labels.push(label);
-    }
-    else
-    {
-        let label = format!("{} - {} B", directory, num);
-        combined_labels.push(label);
-    }
-    }
-
-    for directory in &combined_labels {
-                   ui.label(directory);
-               }
     }
     else
     {
     ui.label("No small directories found.");
     }
 });
+            if let (Some(listing), false) = (&self.archive, self.archive_children.is_empty()) {
+                ui.separator();
+                archives::show_children(ui, listing, &self.archive_children);
+            }
+            match action {
+                Some(SectorAction::Open(path)) => {
+                    self.path = path;
+                    self.scanning_path = self.path.clone();
+                    self.update_pie_chart_data();
+                }
+                //entries inside an archive aren't on disk, so there is nothing to remove, hold open or sample
+                Some(SectorAction::Trash(path) | SectorAction::Delete(path) | SectorAction::Handles(path) | SectorAction::Compressibility(path)) if fs::symlink_metadata(&path).is_err() => {}
+                Some(SectorAction::Trash(path)) => self.request_removal(path, false),
+                Some(SectorAction::Delete(path)) => self.request_removal(path, true),
+                Some(SectorAction::Compressibility(path)) => {
+                    self.compressibility = Some(compressibility::estimate(Path::new(&path), &self.path_rules));
+                    self.show_compressibility = true;
+                }
+                Some(SectorAction::Handles(path)) => {
+                    self.handles = open_files::handles_in(Path::new(&path));
+                    self.handles_path = Some(path);
+                }
+                None => {}
+            }
  
         });
+        self.confirm_removal(ctx);
+        let mut show_owners = self.show_owners;
+        egui::Window::new("Usage by owner").open(&mut show_owners).show(ctx, |ui| {
+            if let Some(uid) = owners::show(ui, &self.owner_usage, &self.owner_names) {
+                self.filters.set_owner(Some(uid));
+                self.update_pie_chart_data();
+            }
+        });
+        self.show_owners = show_owners;
+        let mut show_trash = self.show_trash;
+        egui::Window::new("Trash").open(&mut show_trash).show(ctx, |ui| {
+            self.trash.show(ui);
+        });
+        self.show_trash = show_trash;
+        let mut show_duplicates = self.show_duplicates;
+        egui::Window::new("Duplicates").open(&mut show_duplicates).show(ctx, |ui| {
+            duplicate_finder::show(ui, &self.duplicate_scan, &self.scanning_path, &self.path_rules);
+        });
+        self.show_duplicates = show_duplicates;
+        let mut show_rules = self.show_rules;
+        egui::Window::new("Recommendation rules").open(&mut show_rules).show(ctx, |ui| {
+            match self.rules.try_lock() {
+                Ok(mut rules) => rules.show(ui),
+                Err(_) => {
+                    ui.label("The rules are running, they can be changed once they are done.");
+                }
+            }
+        });
+        self.show_rules = show_rules;
+        self.recommendations.poll(&self.rules);
+        let mut show_recommendations = self.show_recommendations;
+        let mut event = None;
+        egui::Window::new("Recommendations").open(&mut show_recommendations).show(ctx, |ui| {
+            event = self.recommendations.show(ui, &self.path_rules);
+        });
+        self.show_recommendations = show_recommendations;
+        match event {
+            Some(recommendations::RecommendationEvent::Navigate(path)) => {
+                let directory = if path.is_dir() { path.as_path() } else { path.parent().unwrap_or(&path) };
+                self.path = directory.to_string_lossy().to_string();
+                self.scanning_path = self.path.clone();
+                self.update_pie_chart_data();
+            }
+            Some(recommendations::RecommendationEvent::Reclaimed) => self.update_pie_chart_data(),
+            None => {}
+        }
+        let mut show_build_artifacts = self.show_build_artifacts;
+        egui::Window::new("Build artifacts").open(&mut show_build_artifacts).show(ctx, |ui| {
+            self.build_artifacts.show(ui, &self.scanning_path, &self.path_rules);
+        });
+        self.show_build_artifacts = show_build_artifacts;
+        let mut show_caches = self.show_caches;
+        egui::Window::new("Caches").open(&mut show_caches).show(ctx, |ui| {
+            self.caches.show(ui, &self.path_rules);
+        });
+        self.show_caches = show_caches;
+        let mut show_filesystems = self.show_filesystems;
+        let mut scan = None;
+        egui::Window::new("Filesystems").open(&mut show_filesystems).show(ctx, |ui| {
+            scan = filesystems::show(ui, &mut self.filesystems, &mut self.all_filesystems);
+        });
+        self.show_filesystems = show_filesystems;
+        let mut show_block_devices = self.show_block_devices;
+        egui::Window::new("Block devices").open(&mut show_block_devices).show(ctx, |ui| {
+            if let Some(mount_point) = block_devices::show(ui, &mut self.block_devices) {
+                scan = Some(mount_point);
+            }
+        });
+        self.show_block_devices = show_block_devices;
+        let mut show_deleted_files = self.show_deleted_files;
+        egui::Window::new("Deleted but open files").open(&mut show_deleted_files).show(ctx, |ui| {
+            self.deleted_files.show(ui);
+        });
+        self.show_deleted_files = show_deleted_files;
+        let mut show_archives = self.show_archives;
+        egui::Window::new("Archives by format").open(&mut show_archives).show(ctx, |ui| {
+            archives::show(ui, &self.scanning_path, &self.path_rules, &mut self.archive_totals);
+        });
+        self.show_archives = show_archives;
+        let mut show_compressibility = self.show_compressibility;
+        egui::Window::new("Compression estimate").open(&mut show_compressibility).show(ctx, |ui| {
+            compressibility::show(ui, &mut self.compressibility, &self.path_rules);
+        });
+        self.show_compressibility = show_compressibility;
+        let mut show_path_rules = self.show_path_rules;
+        let mut excludes_changed = false;
+        egui::Window::new("Excludes and protected paths").open(&mut show_path_rules).show(ctx, |ui| {
+            excludes_changed = path_rules::show(ui, &mut self.path_rules, &mut self.new_exclude, &mut self.new_protected);
+        });
+        self.show_path_rules = show_path_rules;
+        if excludes_changed {
+            self.update_pie_chart_data();
+        }
+        let mut show_io_monitor = self.show_io_monitor;
+        egui::Window::new("Disk I/O by process").open(&mut show_io_monitor).show(ctx, |ui| {
+            self.io_monitor.show(ui);
+        });
+        self.show_io_monitor = show_io_monitor;
+        let mut show_throughput = self.show_throughput;
+        egui::Window::new("Device throughput").open(&mut show_throughput).show(ctx, |ui| {
+            self.throughput.show(ui);
+        });
+        self.show_throughput = show_throughput;
+        if let Some(path) = self.handles_path.clone() {
+            let mut open = true;
+            egui::Window::new("Open handles").open(&mut open).show(ctx, |ui| {
+                open_files::show_handles(ui, &path, &mut self.handles);
+            });
+            if !open {
+                self.handles_path = None;
+            }
+        }
+        if let Some(mount_point) = scan {
+            self.path = mount_point.to_string_lossy().to_string();
+            self.scanning_path = self.path.clone();
+            self.update_pie_chart_data();
+        }
     }
 }
 
 impl MyApp {
 
-fn recommendations(&self)->Result<(), std::io::Error> {
-        let trash_path= dirs::home_dir()
-        .map(|home| home.join(".local/share/Trash"))
-        .expect("Could not determine home directory");
-        let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("recommendations.txt")?;
-        file.set_len(0)?;
-        //fs::remove_file("recommendations.txt")?;
-        writeln!(file, "Directories to consider:")?;
-        // Append data to the file
-        if !is_directory_empty(&trash_path) {
-            writeln!(file, "- Empty the trash")?;
-        }
-        let base_dir = dirs::home_dir().map(|home| home.join(""))
-        .expect("Could not determine home directory");
-        let one_month_ago = SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60);
-        for entry in WalkDir::new(&base_dir).into_iter().filter_map(|e| e.ok()) {
-            let path = entry.path();
-            // Check if it is a directory
-            if path.is_dir() {
-                // Get metadata
-                if let Ok(metadata) = fs::metadata(path) {
-                    // Check access time
-                    if let Ok(access_time) = metadata.accessed() {
-                        if access_time < one_month_ago {
-                            writeln!(file,"- Directory accessed more than a month ago: {:?}", path);
-                            // Add logic to process or store the path as needed
-                        }
-                    }
-                }
+fn new(cc: &eframe::CreationContext) -> Self {
+    let mut app = Self::default();
+    app.apply_settings(settings::load(cc.storage), &cc.egui_ctx);
+    app
+}
+
+fn settings(&self) -> settings::Settings {
+    settings::Settings {
+        path: self.path.clone(),
+        filters: self.filters.clone(),
+        path_rules: self.path_rules.clone(),
+        sorted: self.sorted,
+        whole_disk: self.whole_disk,
+        dark_mode: self.dark_mode,
+    }
+}
+
+fn apply_settings(&mut self, settings: settings::Settings, ctx: &egui::Context) {
+    self.path = settings.path;
+    self.scanning_path = self.path.clone();
+    self.filters = settings.filters;
+    self.path_rules = settings.path_rules;
+    self.sorted = settings.sorted;
+    self.whole_disk = settings.whole_disk;
+    self.dark_mode = settings.dark_mode;
+    ctx.set_visuals(if self.dark_mode { Visuals::dark() } else { Visuals::light() });
+}
+
+fn request_removal(&mut self, path: String, permanent: bool) {
+    let (size, items, owners) = summarize_path(&path);
+    let handles = open_files::handles_in(Path::new(&path));
+    let protected_by = self.path_rules.protecting(Path::new(&path)).map(|protected| protected.to_string());
+    self.pending_removal = Some(PendingRemoval { path, size, items, owners, permanent, protected_by, handles });
+}
+
+fn confirm_removal(&mut self, ctx: &egui::Context) {
+    let Some(removal) = self.pending_removal.clone() else { return };
+    let verb = if removal.permanent { "Delete permanently" } else { "Move to trash" };
+    let mut confirmed = false;
+    let mut cancelled = false;
+    egui::Window::new("Confirm")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(format!("{} {}?", verb, removal.path));
+            ui.label(format!("{} in {} items", format_size(removal.size), removal.items));
+            if removal.permanent {
+                ui.colored_label(egui::Color32::RED, "This cannot be undone.");
             }
-        }
-        let current_path = self.scanning_path.clone();
-        let base_path = PathBuf::from(current_path);
-        let descendants = get_descendants(&base_path);
-        let duplicates = get_duplicates_hashed(&descendants);
-        for row in duplicates.iter() {
-        // Iterate over elements in each row
-            writeln!(file, "Duplicates:");
-            for element in row.iter() {
-                // Do something with the element
-                writeln!(file, "- {:?}", element);
+            if !removal.handles.is_empty() {
+                let mut processes: Vec<String> = removal.handles.iter().map(|handle| format!("{} ({})", handle.process, handle.pid)).collect();
+                processes.dedup();
+                ui.colored_label(egui::Color32::YELLOW, format!("Still in use by {}", processes.join(", ")))
+                    .on_hover_text("Their open files keep taking space until they are closed");
+            }
+            if let Some(protected) = &removal.protected_by {
+                ui.colored_label(egui::Color32::RED, path_rules::protected_hint(protected));
             }
+            ui.horizontal(|ui| {
+                confirmed = ui.add_enabled(removal.protected_by.is_none(), egui::Button::new(verb)).clicked();
+                cancelled = ui.button("Cancel").clicked();
+            });
+        });
+    if cancelled {
+        self.pending_removal = None;
+    } else if confirmed {
+        self.pending_removal = None;
+        let result = if removal.permanent {
+            trash::delete_permanently(Path::new(&removal.path))
+        } else {
+            trash::move_to_trash(Path::new(&removal.path)).map(|_| ())
+        };
+        match result {
+            Ok(()) => self.forget_entry(&removal),
+            Err(e) => eprintln!("Error removing {}: {:?}", removal.path, e),
         }
-        Ok(())
     }
+}
+
+//drops a removed entry from the cached scan and redraws, instead of scanning everything again
+fn forget_entry(&mut self, removal: &PendingRemoval) {
+    self.entries.retain(|(_, _, path)| path != &removal.path);
+    self.owner_usage.subtract(&removal.owners);
+    self.build_pie_chart();
+}
+
+fn recommendations(&mut self, ctx: &egui::Context) {
+    self.recommendations.start(self.rules.clone(), PathBuf::from(&self.scanning_path), self.path_rules.clone(), ctx.clone());
+    self.show_recommendations = true;
+}
 fn create_file(&self) -> Result<(), std::io::Error> {
-       let mut file = File::create("disk_space.txt")?;
-    let mut sys = System::new_all();
-    sys.refresh_all();
-// Get the current disk
-       let output = Command::new("df")
-        .arg("-h").arg("-T") // Human-readable output
-        .output()
-        .expect("Failed to execute command");
-
-    // Check if the command succeeded
-    if output.status.success() {
-        // Write the output to the file
-        file.write_all(&output.stdout)?;
-    } else {
-        // If the command failed, write the error message to the file
-        let error_msg = format!("Error: {}", String::from_utf8_lossy(&output.stderr));
-        file.write_all(error_msg.as_bytes())?;
+    let path = Path::new("disk_space.txt");
+    filesystems::write_report(path)?;
+    //the scanned entries follow, with the filters the chart uses
+    let mut file = fs::OpenOptions::new().append(true).open(path)?;
+    writeln!(file)?;
+    writeln!(file, "{} (filters: {})", self.scanning_path, self.filters.describe(&self.owner_names))?;
+    let mut entries = self.entries.clone();
+    entries.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    for (size, name, _) in entries {
+        writeln!(file, "{:>12} {}", format_size(size), name)?;
     }
     Ok(())
-       
-    }
-    fn update_pie_chart_data(&mut self,ui: &mut egui::Ui) {
-        let mut total_size = 0.0;
+}
+    fn update_pie_chart_data(&mut self) {
         let mut file_data: Vec<(f64, String, String)> = Vec::new(); // Vector to store file name and size pairs
        if self.scanning_path=="others"
        {
         
+       }
+       else if let Some((archive, inner)) = archives::split(Path::new(&self.scanning_path))
+       {
+        self.browse_archive(archive, inner);
        }
        else
        {
+        self.archive = None;
+        self.archive_children.clear();
+        self.owner_usage.clear();
+        let meter = throughput::ScanMeter::start();
         for entry_result in WalkDir::new(&self.scanning_path).max_depth(1).into_iter() {
             match entry_result {
                 Ok(entry) => {
-                if ((entry.file_type().is_dir() && !is_hidden(&entry)) || (self.hidden && is_hidden(&entry)) || (self.compressed && is_compressed_file(&entry))) && entry.path() != Path::new(&self.scanning_path) {
+                if entry.depth() > 0 && !self.path_rules.is_excluded(entry.path()) && self.filters.keeps_entry(&entry.file_name().to_string_lossy(), entry.file_type().is_dir()) {
                     let file_name = entry.file_name().to_string_lossy().to_string();
                     let entry_path = entry.path().to_string_lossy().to_string();
-                    let size = calculate_directory_size(entry.path().to_str().unwrap());
+                    let size = calculate_directory_size(entry.path().to_str().unwrap(), &mut self.owner_usage, &self.filters, &self.path_rules);
                     match size {
+                        //entries with nothing passing the chips would only clutter the list
+                        Ok(f) if f == 0.0 && !self.filters.chips.is_empty() => {}
                         Ok(f) => {
                             // The f64 value is in the Ok variant
-                            total_size = total_size + f;
                             file_data.push((f, file_name, entry_path));
                         }
                         Err(e) => {
@@ -511,49 +929,114 @@ fn create_file(&self) -> Result<(), std::io::Error> {
             }
             }
         }
+        self.throughput.last_scan = Some(meter.finish(&self.scanning_path));
+        self.entries = file_data;
+        self.build_pie_chart();
+    }
+    }
+
+    //shows a directory inside an archive as if it were on disk, sized by the uncompressed contents
+    fn browse_archive(&mut self, archive: PathBuf, mut inner: String) {
+        if self.archive.as_ref().map(|listing| listing.archive != archive).unwrap_or(true) {
+            match archives::read_listing(&archive) {
+                Ok(listing) => self.archive = Some(listing),
+                Err(e) => {
+                    eprintln!("Error reading archive {:?}: {:?}", archive, e);
+                    self.archive = None;
+                }
+            }
+        }
+        self.owner_usage.clear();
+        self.archive_children.clear();
+        if let Some(listing) = &self.archive {
+            //a file inside the archive has nothing to show, so its directory stays open
+            if listing.is_file(&inner) {
+                inner = inner.rsplit_once('/').map(|(parent, _)| parent.to_string()).unwrap_or_default();
+                let directory = if inner.is_empty() { archive.clone() } else { archive.join(&inner) };
+                self.path = directory.to_string_lossy().to_string();
+                self.scanning_path = self.path.clone();
+            }
+            self.archive_children = listing.children(&inner).into_iter().filter(|child| self.filters.keeps_entry(&child.name, child.is_dir)).collect();
+        }
+        self.entries = self
+            .archive_children
+            .iter()
+            .map(|child| (child.size as f64, child.name.clone(), Path::new(&self.scanning_path).join(&child.name).to_string_lossy().to_string()))
+            .collect();
+        self.build_pie_chart();
+    }
+
+    //splits the cached entries into pie sectors and the "others" list
+    fn build_pie_chart(&mut self) {
+        let mut file_data = self.entries.clone();
         if self.sorted {
             file_data.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
         }
+        let total_size: f64 = file_data.iter().map(|(size, _, _)| size).sum();
         let smallest_size = total_size/360.0; //smallest size a dir can be
         let mut clean_file_data: Vec<(f64, String, String)> = Vec::new(); // Vector to store file name and size pairs, only dirs > 1/360th total size
         let mut small_file_data: Vec<(f64, String, String)> = Vec::new(); // Vector to store file name and size pairs, only smallers dirs
 
-        for i in 0..file_data.len(){
-            if file_data[i].0 > smallest_size{
-                clean_file_data.push((file_data[i].0, file_data[i].1.to_string(), file_data[i].2.to_string()));
+        for data in file_data {
+            if data.0 > smallest_size{
+                clean_file_data.push(data);
             }
             else{
-                small_file_data.push((file_data[i].0, file_data[i].1.to_string(), file_data[i].2.to_string()));
+                small_file_data.push(data);
             }
            
         }
         let mut total_small=0.0; 
-        for i in 0..small_file_data.len()
+        for data in &small_file_data
         {
-        total_small=total_small + small_file_data[i].0; 
+        total_small += data.0; 
         }
         if total_small>0.0 
         { if total_small<smallest_size 
         { total_small =smallest_size; }
         clean_file_data.push((total_small, ("others").to_string(), ("others").to_string()));
         }
+        if self.whole_disk {
+            clean_file_data.extend(self.disk_slices());
+        }
 
         //Draw box here:
 
-        let mut radius = ui.available_size().x.min(ui.available_size().y) / 1200.0;
-        self.pie_chart = PieChart::new("Pie Chart", &clean_file_data, self.radius.into());
+        self.pie_chart = PieChart::new("Pie Chart", &clean_file_data, self.radius);
         self.small_directories = small_file_data.iter().map(|(_, name, _)| name.clone()).collect();
-         self.small_directories2 = small_file_data.iter().map(|(size, _, _)| size.clone()).collect();
+         self.small_directories2 = small_file_data.iter().map(|(size, _, _)| *size).collect();
     }
+
+    //free space and the used space the scan didn't see (reserved blocks, other users' files, deleted
+    //but open files), only when the scanned path is the root of a filesystem
+    fn disk_slices(&self) -> Vec<(f64, String, String)> {
+        let root = Path::new(&self.scanning_path);
+        if mounts::mount_of(root).map(|mount| mount.mount_point != root).unwrap_or(true) {
+            return Vec::new();
+        }
+        let (Ok(usage), Ok(root_metadata)) = (mounts::usage(root), fs::metadata(root)) else { return Vec::new() };
+        //entries mounted from other filesystems (/proc, a separate /home...) take no space here
+        let scanned: f64 = self
+            .entries
+            .iter()
+            .filter(|(_, _, path)| fs::symlink_metadata(path).map(|metadata| metadata.dev() == root_metadata.dev()).unwrap_or(false))
+            .map(|(size, _, _)| size)
+            .sum();
+        let mut slices = vec![(usage.available as f64, FREE_SPACE.to_string(), FREE_SPACE.to_string())];
+        let unaccounted = (usage.total - usage.available) as f64 - scanned;
+        if unaccounted > 0.0 {
+            slices.push((unaccounted, UNACCOUNTED.to_string(), UNACCOUNTED.to_string()));
+        }
+        slices
     }
 }
 
 fn main() -> Result<(), eframe::Error> {
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
     let options = eframe::NativeOptions {
-        initial_window_size: Some(egui::vec2(10000.0, 10000.0)),
+        initial_window_size: Some(DEFAULT_WINDOW_SIZE),
         ..Default::default()
     };
-    eframe::run_native("DISK ANALYZER", options, Box::new(|ctx| Box::<MyApp>::default()))?;
+    eframe::run_native("DISK ANALYZER", options, Box::new(|cc| Box::new(MyApp::new(cc))))?;
     Ok(())
 }
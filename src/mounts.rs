@@ -0,0 +1,151 @@
+use std::ffi::CString;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+//one line of /proc/self/mountinfo
+#[derive(Clone)]
+pub struct Mount {
+    //major:minor of the device, which links mounts to /sys/block and /proc/diskstats
+    pub device_number: String,
+    pub mount_point: PathBuf,
+    pub options: Vec<String>,
+    pub fs_type: String,
+    pub source: String,
+}
+
+impl Mount {
+    pub fn has_option(&self, option: &str) -> bool {
+        self.options.iter().any(|o| o == option)
+    }
+}
+
+//kernel and virtual filesystems that hold no files of their own
+const PSEUDO_FILESYSTEMS: [&str; 22] = [
+    "proc", "sysfs", "devtmpfs", "devpts", "cgroup", "cgroup2", "securityfs", "pstore", "debugfs", "tracefs",
+    "configfs", "fusectl", "mqueue", "hugetlbfs", "bpf", "autofs", "binfmt_misc", "efivarfs", "rpc_pipefs",
+    "nsfs", "ramfs", "squashfs",
+];
+
+pub fn is_pseudo(fs_type: &str) -> bool {
+    PSEUDO_FILESYSTEMS.contains(&fs_type)
+}
+
+//fields are "id parent major:minor root mount_point options [optional...] - fs_type source super_options"
+pub fn mounts() -> Vec<Mount> {
+    fs::read_to_string("/proc/self/mountinfo")
+        .map(|mountinfo| {
+            mountinfo
+                .lines()
+                .filter_map(|line| {
+                    let (before, after) = line.split_once(" - ")?;
+                    let fields: Vec<&str> = before.split_whitespace().collect();
+                    let after: Vec<&str> = after.split_whitespace().collect();
+                    if fields.len() < 6 || after.len() < 2 {
+                        return None;
+                    }
+                    Some(Mount {
+                        device_number: fields[2].to_string(),
+                        mount_point: PathBuf::from(unescape(fields[4])),
+                        options: fields[5].split(',').map(|option| option.to_string()).collect(),
+                        fs_type: after[0].to_string(),
+                        source: unescape(after[1]),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn mount_points() -> Vec<PathBuf> {
+    mounts().into_iter().map(|mount| mount.mount_point).collect()
+}
+
+//the mount a path lives on, which is the one with the longest matching mount point
+pub fn mount_of(path: &Path) -> Option<Mount> {
+    mounts()
+        .into_iter()
+        .filter(|mount| path.starts_with(&mount.mount_point))
+        .max_by_key(|mount| mount.mount_point.as_os_str().len())
+}
+
+//spaces and other special characters in mount tables are written as \ooo octal escapes
+pub fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 4 <= bytes.len() {
+            if let Some(byte) = std::str::from_utf8(&bytes[i + 1..i + 4]).ok().and_then(|octal| u8::from_str_radix(octal, 8).ok()) {
+                decoded.push(byte);
+                i += 4;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+//space and inode counts of a filesystem, from statvfs
+#[derive(Clone, Copy, Default)]
+pub struct FsUsage {
+    pub total: u64,
+    pub free: u64,
+    //free space for unprivileged users, less than free by the reserved blocks
+    pub available: u64,
+    pub inodes: u64,
+    pub inodes_free: u64,
+}
+
+impl FsUsage {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free)
+    }
+
+    //used share like df computes it, against what non-root users can get
+    pub fn used_fraction(&self) -> f32 {
+        let usable = self.used() + self.available;
+        if usable == 0 { 0.0 } else { self.used() as f32 / usable as f32 }
+    }
+
+    pub fn inodes_used_fraction(&self) -> f32 {
+        if self.inodes == 0 { 0.0 } else { self.inodes.saturating_sub(self.inodes_free) as f32 / self.inodes as f32 }
+    }
+}
+
+pub fn usage(path: &Path) -> Result<FsUsage, std::io::Error> {
+    let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidInput))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let block = stat.f_frsize as u64;
+    Ok(FsUsage {
+        total: stat.f_blocks as u64 * block,
+        free: stat.f_bfree as u64 * block,
+        available: stat.f_bavail as u64 * block,
+        inodes: stat.f_files as u64,
+        inodes_free: stat.f_ffree as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unescape;
+
+    #[test]
+    fn unescape_decodes_octal_escapes() {
+        assert_eq!(unescape("/mnt/my\\040disk"), "/mnt/my disk");
+        assert_eq!(unescape("tab\\011and\\134backslash"), "tab\tand\\backslash");
+        assert_eq!(unescape("/media/\\303\\274"), "/media/ü");
+    }
+
+    #[test]
+    fn unescape_keeps_incomplete_escapes() {
+        assert_eq!(unescape("/plain/path"), "/plain/path");
+        assert_eq!(unescape("end\\04"), "end\\04");
+        assert_eq!(unescape("not\\999octal"), "not\\999octal");
+    }
+}
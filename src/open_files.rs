@@ -0,0 +1,263 @@
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use sysinfo::{PidExt, ProcessExt, ProcessRefreshKind, System, SystemExt};
+
+//a process, the files its descriptors point at and its working directory, from /proc/<pid>
+pub struct ProcessFiles {
+    pub pid: u32,
+    pub name: String,
+    pub cmdline: String,
+    pub fds: Vec<(u32, PathBuf)>,
+    pub cwd: Option<PathBuf>,
+}
+
+//every process we may look into; other users' descriptors are only readable as root
+pub fn processes() -> Vec<ProcessFiles> {
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessRefreshKind::new());
+    let mut processes: Vec<ProcessFiles> = system
+        .processes()
+        .values()
+        .map(|process| {
+            let pid = process.pid().as_u32();
+            ProcessFiles {
+                pid,
+                name: process.name().to_string(),
+                cmdline: process.cmd().join(" "),
+                fds: fds(pid),
+                cwd: fs::read_link(format!("/proc/{}/cwd", pid)).ok(),
+            }
+        })
+        .collect();
+    processes.sort_by_key(|process| process.pid);
+    processes
+}
+
+fn fds(pid: u32) -> Vec<(u32, PathBuf)> {
+    let Ok(entries) = fs::read_dir(format!("/proc/{}/fd", pid)) else { return Vec::new() };
+    entries
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let fd = entry.file_name().to_str()?.parse().ok()?;
+            Some((fd, fs::read_link(entry.path()).ok()?))
+        })
+        .collect()
+}
+
+//a process using something below a directory, either through an open file or by running inside it
+#[derive(Clone)]
+pub struct Handle {
+    pub pid: u32,
+    pub process: String,
+    pub cmdline: String,
+    //the open file, or the working directory
+    pub path: PathBuf,
+    pub is_cwd: bool,
+}
+
+pub fn handles_in(dir: &Path) -> Vec<Handle> {
+    let mut handles = Vec::new();
+    for process in processes() {
+        let handle = |path: &Path, is_cwd: bool| Handle {
+            pid: process.pid,
+            process: process.name.clone(),
+            cmdline: process.cmdline.clone(),
+            path: path.to_path_buf(),
+            is_cwd,
+        };
+        if let Some(cwd) = process.cwd.as_deref().filter(|cwd| cwd.starts_with(dir)) {
+            handles.push(handle(cwd, true));
+        }
+        for (_, target) in &process.fds {
+            //a deleted file is no longer in the directory, whatever its old path says
+            if target.starts_with(dir) && !target.to_string_lossy().ends_with(" (deleted)") {
+                handles.push(handle(target, false));
+            }
+        }
+    }
+    handles
+}
+
+pub fn show_handles(ui: &mut egui::Ui, dir: &str, handles: &mut Vec<Handle>) {
+    ui.horizontal(|ui| {
+        if ui.button("Refresh").clicked() {
+            *handles = handles_in(Path::new(dir));
+        }
+        ui.label(format!("{} open handles in {}", handles.len(), dir));
+    });
+    ui.separator();
+    egui::ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+        egui::Grid::new("open_handles").striped(true).show(ui, |ui| {
+            for heading in ["Process", "PID", "Using", "Command line"] {
+                ui.strong(heading);
+            }
+            ui.end_row();
+            for handle in handles.iter() {
+                ui.label(&handle.process);
+                ui.label(handle.pid.to_string());
+                if handle.is_cwd {
+                    ui.label(format!("working directory {}", handle.path.display()));
+                } else {
+                    ui.label(handle.path.to_string_lossy());
+                }
+                let short: String = handle.cmdline.chars().take(60).collect();
+                ui.label(short).on_hover_text(&handle.cmdline);
+                ui.end_row();
+            }
+        });
+    });
+}
+
+//a file removed from its directory that stays on disk as long as some process keeps it open
+pub struct DeletedFile {
+    pub pid: u32,
+    pub fd: u32,
+    pub process: String,
+    pub cmdline: String,
+    pub path: String,
+    pub size: u64,
+    //the same file open in several places is only counted once in the total
+    pub inode: (u64, u64),
+}
+
+impl DeletedFile {
+    fn fd_path(&self) -> String {
+        format!("/proc/{}/fd/{}", self.pid, self.fd)
+    }
+
+    //frees the space while the process keeps its descriptor, which then points at an empty file
+    pub fn truncate(&self) -> Result<(), std::io::Error> {
+        OpenOptions::new().write(true).open(self.fd_path())?.set_len(0)
+    }
+
+    pub fn signal(&self, signal: libc::c_int) -> Result<(), std::io::Error> {
+        if unsafe { libc::kill(self.pid as libc::pid_t, signal) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+//the kernel marks the link target of a deleted file with " (deleted)"; memfd and other
+//anonymous files look the same, but they don't live in the filesystem
+pub fn find_deleted() -> Vec<DeletedFile> {
+    let mut files = Vec::new();
+    for process in processes() {
+        for (fd, target) in &process.fds {
+            let target = target.to_string_lossy();
+            let Some(path) = target.strip_suffix(" (deleted)") else { continue };
+            if !path.starts_with('/') || path.starts_with("/memfd:") || path.starts_with("/dev/") {
+                continue;
+            }
+            let Ok(metadata) = fs::metadata(format!("/proc/{}/fd/{}", process.pid, fd)) else { continue };
+            if !metadata.is_file() {
+                continue;
+            }
+            files.push(DeletedFile {
+                pid: process.pid,
+                fd: *fd,
+                process: process.name.clone(),
+                cmdline: process.cmdline.clone(),
+                path: path.to_string(),
+                size: metadata.blocks() * 512,
+                inode: (metadata.dev(), metadata.ino()),
+            });
+        }
+    }
+    files.sort_by_key(|file| std::cmp::Reverse(file.size));
+    files
+}
+
+pub fn total_size(files: &[DeletedFile]) -> u64 {
+    let mut seen = HashSet::new();
+    files.iter().filter(|file| seen.insert(file.inode)).map(|file| file.size).sum()
+}
+
+enum Fix {
+    Truncate,
+    Terminate,
+}
+
+#[derive(Default)]
+pub struct DeletedFilesPanel {
+    pub files: Vec<DeletedFile>,
+    confirming: Option<(usize, Fix)>,
+}
+
+impl DeletedFilesPanel {
+    pub fn refresh(&mut self) {
+        self.files = find_deleted();
+        self.confirming = None;
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("Refresh").clicked() {
+                self.refresh();
+            }
+            ui.label(format!("{} deleted files still open, holding {}", self.files.len(), crate::format_size(total_size(&self.files) as f64)));
+        });
+        if let Some((index, fix)) = &self.confirming {
+            let file = &self.files[*index];
+            let mut done = false;
+            let mut cancelled = false;
+            ui.group(|ui| {
+                match fix {
+                    Fix::Truncate => {
+                        ui.label(format!("Truncate {} held open by {} ({})?", file.path, file.process, file.pid));
+                        ui.colored_label(egui::Color32::RED, "Its contents are lost and the process may misbehave.");
+                    }
+                    Fix::Terminate => {
+                        ui.label(format!("Send SIGTERM to {} ({}) so it closes {}?", file.process, file.pid, file.path));
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Confirm").clicked() {
+                        let result = match fix {
+                            Fix::Truncate => file.truncate(),
+                            Fix::Terminate => file.signal(libc::SIGTERM),
+                        };
+                        if let Err(e) = result {
+                            eprintln!("Error freeing {}: {:?}", file.path, e);
+                        }
+                        done = true;
+                    }
+                    cancelled = ui.button("Cancel").clicked();
+                });
+            });
+            if done {
+                self.refresh();
+            } else if cancelled {
+                self.confirming = None;
+            }
+        }
+        ui.separator();
+        egui::ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+            egui::Grid::new("deleted_files").striped(true).show(ui, |ui| {
+                for heading in ["Size", "File", "Process", "PID", "Command line", ""] {
+                    ui.strong(heading);
+                }
+                ui.end_row();
+                for (index, file) in self.files.iter().enumerate() {
+                    ui.label(crate::format_size(file.size as f64));
+                    ui.label(&file.path);
+                    ui.label(&file.process);
+                    ui.label(file.pid.to_string());
+                    let short: String = file.cmdline.chars().take(60).collect();
+                    ui.label(short).on_hover_text(&file.cmdline);
+                    ui.horizontal(|ui| {
+                        if ui.button("Truncate").clicked() {
+                            self.confirming = Some((index, Fix::Truncate));
+                        }
+                        if ui.button("Terminate").clicked() {
+                            self.confirming = Some((index, Fix::Terminate));
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        });
+    }
+}
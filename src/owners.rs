@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::fs;
+use egui::plot::{Bar, BarChart, Plot};
+
+//sizes collected per uid and gid while a scan walks the tree
+#[derive(Clone, Default)]
+pub struct OwnerUsage {
+    pub by_uid: HashMap<u32, f64>,
+    pub by_gid: HashMap<u32, f64>,
+}
+
+impl OwnerUsage {
+    pub fn add(&mut self, uid: u32, gid: u32, size: f64) {
+        *self.by_uid.entry(uid).or_insert(0.0) += size;
+        *self.by_gid.entry(gid).or_insert(0.0) += size;
+    }
+
+    pub fn subtract(&mut self, other: &OwnerUsage) {
+        for (uid, size) in &other.by_uid {
+            if let Some(total) = self.by_uid.get_mut(uid) {
+                *total = (*total - size).max(0.0);
+            }
+        }
+        for (gid, size) in &other.by_gid {
+            if let Some(total) = self.by_gid.get_mut(gid) {
+                *total = (*total - size).max(0.0);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.by_uid.clear();
+        self.by_gid.clear();
+    }
+}
+
+//names for uids and gids, read from /etc/passwd and /etc/group
+#[derive(Clone, Default)]
+pub struct OwnerNames {
+    pub users: HashMap<u32, String>,
+    pub groups: HashMap<u32, String>,
+}
+
+impl OwnerNames {
+    pub fn load() -> Self {
+        Self {
+            users: read_id_file("/etc/passwd"),
+            groups: read_id_file("/etc/group"),
+        }
+    }
+
+    pub fn user(&self, uid: u32) -> String {
+        self.users.get(&uid).cloned().unwrap_or_else(|| uid.to_string())
+    }
+
+    pub fn group(&self, gid: u32) -> String {
+        self.groups.get(&gid).cloned().unwrap_or_else(|| gid.to_string())
+    }
+}
+
+//both files are "name:password:id:..." per line, so the same parser works for each
+fn read_id_file(file_path: &str) -> HashMap<u32, String> {
+    let mut names = HashMap::new();
+    if let Ok(contents) = fs::read_to_string(file_path) {
+        for line in contents.lines() {
+            if line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(':').collect();
+            if fields.len() < 3 {
+                continue;
+            }
+            if let Ok(id) = fields[2].parse::<u32>() {
+                names.entry(id).or_insert_with(|| fields[0].to_string());
+            }
+        }
+    }
+    names
+}
+
+//largest first, so the chart and table read top-down
+fn sorted_usage(usage: &HashMap<u32, f64>) -> Vec<(u32, f64)> {
+    let mut rows: Vec<(u32, f64)> = usage.iter().map(|(id, size)| (*id, *size)).collect();
+    rows.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    rows
+}
+
+//draws the per-owner bar chart and the user/group tables, returns a uid if one was picked as filter
+pub fn show(ui: &mut egui::Ui, usage: &OwnerUsage, names: &OwnerNames) -> Option<u32> {
+    let mut picked = None;
+    let users = sorted_usage(&usage.by_uid);
+    let groups = sorted_usage(&usage.by_gid);
+
+    let bars: Vec<Bar> = users
+        .iter()
+        .enumerate()
+        .map(|(i, (uid, size))| Bar::new(i as f64, size / (1024.0 * 1024.0)).name(names.user(*uid)))
+        .collect();
+    Plot::new("Owners")
+        .height(200.0)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .show_x(false)
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(BarChart::new(bars).name("MB per owner"));
+        });
+
+    ui.columns(2, |columns| {
+        columns[0].heading("Users");
+        egui::Grid::new("owner_users").striped(true).show(&mut columns[0], |ui| {
+            for (uid, size) in &users {
+                if ui.link(names.user(*uid)).on_hover_text("Show only this owner's files").clicked() {
+                    picked = Some(*uid);
+                }
+                ui.label(uid.to_string());
+                ui.label(crate::format_size(*size));
+                ui.end_row();
+            }
+        });
+        columns[1].heading("Groups");
+        egui::Grid::new("owner_groups").striped(true).show(&mut columns[1], |ui| {
+            for (gid, size) in &groups {
+                ui.label(names.group(*gid));
+                ui.label(gid.to_string());
+                ui.label(crate::format_size(*size));
+                ui.end_row();
+            }
+        });
+    });
+    picked
+}
@@ -0,0 +1,136 @@
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::filters::glob_match;
+
+//paths left out of every scan, and paths nothing may suggest removing or remove
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PathRules {
+    //a pattern with a slash is a path ("~/" allowed, globs too), without one it matches any file or directory name
+    pub excludes: Vec<String>,
+    //paths, "~/" allowed
+    pub protected: Vec<String>,
+}
+
+impl Default for PathRules {
+    fn default() -> Self {
+        Self { excludes: vec![".snapshots".to_string()], protected: vec!["~/Documents".to_string()] }
+    }
+}
+
+fn expand(path: &str) -> PathBuf {
+    crate::caches::expand(path).unwrap_or_else(|| PathBuf::from(path))
+}
+
+impl PathRules {
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        self.excludes.iter().any(|pattern| {
+            if pattern.contains('/') {
+                let pattern = expand(pattern);
+                path.starts_with(&pattern) || glob_match(&pattern.to_string_lossy(), &path.to_string_lossy())
+            } else {
+                glob_match(pattern, &name)
+            }
+        })
+    }
+
+    //the protected entry covering a path: one it is inside of, or one it contains and would take along
+    pub fn protecting(&self, path: &Path) -> Option<&str> {
+        self.protected
+            .iter()
+            .find(|protected| {
+                let protected = expand(protected);
+                path.starts_with(&protected) || protected.starts_with(path)
+            })
+            .map(|protected| protected.as_str())
+    }
+}
+
+//why a path can't be removed, for hover texts and confirmations
+pub fn protected_hint(protected: &str) -> String {
+    format!("Protected by {}, see Excludes.", protected)
+}
+
+fn edit_list(ui: &mut egui::Ui, id: &str, list: &mut Vec<String>, new_entry: &mut String) -> bool {
+    let mut changed = false;
+    let mut removed = None;
+    egui::Grid::new(id).show(ui, |ui| {
+        for (index, entry) in list.iter().enumerate() {
+            ui.label(entry);
+            if ui.small_button("Remove").clicked() {
+                removed = Some(index);
+            }
+            ui.end_row();
+        }
+    });
+    if let Some(index) = removed {
+        list.remove(index);
+        changed = true;
+    }
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(new_entry);
+        if ui.button("Add").clicked() && !new_entry.trim().is_empty() {
+            list.push(new_entry.trim().to_string());
+            new_entry.clear();
+            changed = true;
+        }
+    });
+    changed
+}
+
+//returns true when the excludes changed, so the chart can be rescanned
+pub fn show(ui: &mut egui::Ui, rules: &mut PathRules, new_exclude: &mut String, new_protected: &mut String) -> bool {
+    ui.heading("Excluded from scans");
+    ui.label("Names like .snapshots or *.iso, or paths like /mnt/backup and ~/datasets");
+    let excludes_changed = edit_list(ui, "excludes", &mut rules.excludes, new_exclude);
+    ui.separator();
+    ui.heading("Protected");
+    ui.label("Never suggested for cleanup and never removed, nor anything containing them");
+    edit_list(ui, "protected", &mut rules.protected, new_protected);
+    excludes_changed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use super::PathRules;
+
+    fn rules(excludes: &[&str], protected: &[&str]) -> PathRules {
+        PathRules {
+            excludes: excludes.iter().map(|pattern| pattern.to_string()).collect(),
+            protected: protected.iter().map(|path| path.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn excludes_match_names_anywhere_and_paths_from_their_start() {
+        let rules = rules(&[".snapshots", "*.iso", "/mnt/backup", "/srv/*/cache"], &[]);
+        assert!(rules.is_excluded(Path::new("/home/user/.snapshots")));
+        assert!(rules.is_excluded(Path::new("/home/user/debian.iso")));
+        assert!(!rules.is_excluded(Path::new("/home/user/debian.iso.part")));
+        assert!(rules.is_excluded(Path::new("/mnt/backup")));
+        assert!(rules.is_excluded(Path::new("/mnt/backup/2024/photos")));
+        assert!(!rules.is_excluded(Path::new("/mnt/backups")));
+        assert!(rules.is_excluded(Path::new("/srv/web/cache")));
+        assert!(!rules.is_excluded(Path::new("/srv/web/data")));
+    }
+
+    #[test]
+    fn protection_covers_contents_and_containing_directories() {
+        let rules = rules(&[], &["/home/user/Documents"]);
+        assert_eq!(rules.protecting(Path::new("/home/user/Documents/taxes.pdf")), Some("/home/user/Documents"));
+        assert_eq!(rules.protecting(Path::new("/home/user/Documents")), Some("/home/user/Documents"));
+        assert_eq!(rules.protecting(Path::new("/home/user")), Some("/home/user/Documents"));
+        assert_eq!(rules.protecting(Path::new("/home/user/Downloads")), None);
+        assert_eq!(rules.protecting(Path::new("/home/user/Documents2")), None);
+    }
+
+    #[test]
+    fn protected_entries_expand_the_home_directory() {
+        let Some(home) = dirs::home_dir() else { return };
+        let rules = rules(&[], &["~/Documents"]);
+        assert_eq!(rules.protecting(&home.join("Documents/notes.txt")), Some("~/Documents"));
+        assert_eq!(rules.protecting(Path::new("/tmp/Documents")), None);
+    }
+}
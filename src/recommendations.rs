@@ -0,0 +1,244 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use native_dialog::FileDialog;
+use crate::path_rules::PathRules;
+use crate::rules::{Finding, RuleContext, RuleSet, SuggestedAction};
+
+//findings of one rule, shown as one collapsible group
+pub struct FindingGroup {
+    pub name: String,
+    pub findings: Vec<Finding>,
+    pub selected: Vec<bool>,
+}
+
+impl FindingGroup {
+    fn reclaimable(&self) -> u64 {
+        self.findings.iter().map(|finding| finding.reclaimable).sum()
+    }
+}
+
+pub enum RecommendationEvent {
+    Navigate(PathBuf),
+    Reclaimed,
+}
+
+//shared with the thread running the rules
+#[derive(Default)]
+struct RuleRun {
+    running: bool,
+    findings: Option<Vec<Finding>>,
+    //cleanup commands of reclaimed findings still running, and what went wrong while reclaiming
+    commands_running: bool,
+    errors: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct RecommendationsPanel {
+    pub groups: Vec<FindingGroup>,
+    confirming: bool,
+    run: Arc<Mutex<RuleRun>>,
+}
+
+impl RecommendationsPanel {
+    //runs the enabled rules on their own thread, since hashing for duplicates and walking the tree would freeze the UI
+    pub fn start(&mut self, rules: Arc<Mutex<RuleSet>>, root: PathBuf, path_rules: PathRules, ctx: egui::Context) {
+        let run = self.run.clone();
+        {
+            let mut state = run.lock().unwrap();
+            if state.running {
+                return;
+            }
+            state.running = true;
+            state.errors.clear();
+        }
+        std::thread::spawn(move || {
+            let findings = rules.lock().unwrap().run(&RuleContext { root: &root, path_rules: &path_rules });
+            let mut state = run.lock().unwrap();
+            state.running = false;
+            state.findings = Some(findings);
+            ctx.request_repaint();
+        });
+    }
+
+    //takes the findings of a finished run; the rules are only locked then, so a running thread never blocks the UI
+    pub fn poll(&mut self, rules: &Mutex<RuleSet>) {
+        let findings = self.run.lock().unwrap().findings.take();
+        if let Some(findings) = findings {
+            self.set_findings(findings, &rules.lock().unwrap());
+        }
+    }
+
+    //groups the findings by rule, keeping the severity order RuleSet::run gave them
+    pub fn set_findings(&mut self, findings: Vec<Finding>, rules: &RuleSet) {
+        self.groups.clear();
+        self.confirming = false;
+        for finding in findings {
+            match self.groups.iter_mut().find(|group| group.findings[0].rule == finding.rule) {
+                Some(group) => {
+                    group.findings.push(finding);
+                    group.selected.push(false);
+                }
+                None => self.groups.push(FindingGroup {
+                    name: rules.name_of(&finding.rule),
+                    findings: vec![finding],
+                    selected: vec![false],
+                }),
+            }
+        }
+    }
+
+    fn selected(&self) -> impl Iterator<Item = &Finding> {
+        self.groups
+            .iter()
+            .flat_map(|group| group.findings.iter().zip(&group.selected))
+            .filter(|(_, selected)| **selected)
+            .map(|(finding, _)| finding)
+    }
+
+    pub fn export(&self, path: &Path) -> Result<(), std::io::Error> {
+        let mut file = File::create(path)?;
+        writeln!(file, "Directories to consider:")?;
+        for group in &self.groups {
+            writeln!(file, "{} ({} reclaimable):", group.name, crate::format_size(group.reclaimable() as f64))?;
+            for finding in &group.findings {
+                writeln!(file, "- [{:?}] {} ({} reclaimable, suggested: {})", finding.severity, finding.message,
+                    crate::format_size(finding.reclaimable as f64), finding.action.describe())?;
+            }
+        }
+        Ok(())
+    }
+
+    //applies the selected findings' actions, keeping the ones that failed or became protected so they can be retried;
+    //commands are handed to a worker thread since package manager cleanups can take minutes
+    fn reclaim(&mut self, path_rules: &PathRules, ctx: &egui::Context) {
+        let mut errors = Vec::new();
+        let mut commands = Vec::new();
+        for group in self.groups.iter_mut() {
+            let mut findings = Vec::new();
+            let mut selected = Vec::new();
+            for (finding, chosen) in group.findings.drain(..).zip(group.selected.drain(..)) {
+                if chosen {
+                    //the protected list may have changed since the rules ran
+                    if let Some(protected) = finding.action.paths().iter().find_map(|path| path_rules.protecting(path)) {
+                        errors.push(format!("Skipped {}: {}", finding.action.describe(), crate::path_rules::protected_hint(protected)));
+                    } else if let SuggestedAction::RunCommand(command) = &finding.action {
+                        commands.push(command.clone());
+                        continue;
+                    } else {
+                        match finding.action.apply() {
+                            Ok(()) => continue,
+                            Err(e) => errors.push(format!("{} failed: {}", finding.action.describe(), e)),
+                        }
+                    }
+                }
+                findings.push(finding);
+                selected.push(chosen);
+            }
+            group.findings = findings;
+            group.selected = selected;
+        }
+        self.groups.retain(|group| !group.findings.is_empty());
+        let run = self.run.clone();
+        {
+            let mut state = run.lock().unwrap();
+            state.errors = errors;
+            if commands.is_empty() {
+                return;
+            }
+            state.commands_running = true;
+        }
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let errors: Vec<String> = commands
+                .iter()
+                .filter_map(|command| crate::caches::run_command(command).err().map(|e| format!("Run `{}` failed: {}", command, e)))
+                .collect();
+            let mut state = run.lock().unwrap();
+            state.commands_running = false;
+            state.errors.extend(errors);
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, path_rules: &PathRules) -> Option<RecommendationEvent> {
+        let mut event = None;
+        let (running, commands_running, errors) = {
+            let state = self.run.lock().unwrap();
+            (state.running, state.commands_running, state.errors.clone())
+        };
+        if running {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Running the rules...");
+            });
+            return event;
+        }
+        if commands_running {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Running cleanup commands...");
+            });
+        }
+        for error in &errors {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        let selected_count = self.selected().count();
+        let selected_size: u64 = self.selected().map(|finding| finding.reclaimable).sum();
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(selected_count > 0 && !commands_running, egui::Button::new(format!("Reclaim selected ({})", crate::format_size(selected_size as f64))))
+                .clicked()
+            {
+                self.confirming = true;
+            }
+            if ui.button("Export...").clicked() {
+                if let Ok(Some(path)) = FileDialog::new().set_filename("recommendations.txt").show_save_single_file() {
+                    if let Err(e) = self.export(&path) {
+                        eprintln!("Error creating file: {:?}", e);
+                    }
+                }
+            }
+        });
+        if self.confirming {
+            ui.group(|ui| {
+                ui.label(format!("Apply {} actions, reclaiming about {}?", selected_count, crate::format_size(selected_size as f64)));
+                ui.colored_label(egui::Color32::RED, "Deleted files cannot be restored.");
+                ui.horizontal(|ui| {
+                    if ui.button("Reclaim").clicked() {
+                        self.reclaim(path_rules, ui.ctx());
+                        self.confirming = false;
+                        event = Some(RecommendationEvent::Reclaimed);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.confirming = false;
+                    }
+                });
+            });
+        }
+        ui.separator();
+        if self.groups.is_empty() {
+            ui.label("Nothing to recommend.");
+        }
+        egui::ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+            for group in self.groups.iter_mut() {
+                let title = format!("{} - {} items, {}", group.name, group.findings.len(), crate::format_size(group.reclaimable() as f64));
+                egui::CollapsingHeader::new(title).id_source(&group.name).show(ui, |ui| {
+                    for (finding, selected) in group.findings.iter().zip(group.selected.iter_mut()) {
+                        ui.horizontal(|ui| {
+                            ui.add_enabled(finding.action.can_apply(), egui::Checkbox::new(selected, ""));
+                            ui.label(format!("{:?}", finding.severity));
+                            ui.label(crate::format_size(finding.reclaimable as f64));
+                            let message = ui.link(&finding.message).on_hover_text(finding.action.describe());
+                            if let (true, Some(path)) = (message.clicked(), &finding.path) {
+                                event = Some(RecommendationEvent::Navigate(path.clone()));
+                            }
+                        });
+                    }
+                });
+            }
+        });
+        event
+    }
+}
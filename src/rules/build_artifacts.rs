@@ -0,0 +1,48 @@
+use super::{Finding, Rule, RuleContext, Severity, SuggestedAction};
+use crate::build_artifacts::find_projects;
+
+//target/, node_modules and friends; projects not built for stale_days are warnings
+pub struct BuildArtifactsRule {
+    pub stale_days: u64,
+}
+
+impl Default for BuildArtifactsRule {
+    fn default() -> Self {
+        Self { stale_days: 30 }
+    }
+}
+
+impl Rule for BuildArtifactsRule {
+    fn id(&self) -> &str {
+        "build_artifacts"
+    }
+
+    fn name(&self) -> &str {
+        "Developer build artifacts"
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<Finding> {
+        find_projects(context.root)
+            .into_iter()
+            .map(|project| {
+                let last_build = project.last_build().map(crate::format_age).unwrap_or_else(|| "never".to_string());
+                Finding {
+                    rule: self.id().to_string(),
+                    severity: if project.is_stale(self.stale_days) { Severity::Warning } else { Severity::Info },
+                    path: Some(project.root.clone()),
+                    message: format!("Build artifacts ({}) in {:?}, last built {}", project.kinds(), project.root, last_build),
+                    reclaimable: project.size(),
+                    action: SuggestedAction::Delete(project.artifacts.iter().map(|artifact| artifact.path.clone()).collect()),
+                }
+            })
+            .collect()
+    }
+
+    fn settings_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Stale after");
+            ui.add(egui::DragValue::new(&mut self.stale_days).clamp_range(1..=3650));
+            ui.label("days");
+        });
+    }
+}
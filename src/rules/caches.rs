@@ -0,0 +1,61 @@
+use super::{Finding, Rule, RuleContext, Severity, SuggestedAction};
+use crate::caches::{find_caches, nested_in};
+
+//package-manager and application caches from the catalog in crate::caches
+pub struct CachesRule {
+    //caches smaller than this are not worth mentioning
+    pub min_size: u64,
+    pub warn_size: u64,
+}
+
+impl Default for CachesRule {
+    fn default() -> Self {
+        Self { min_size: 100 * 1024 * 1024, warn_size: 5 * 1024 * 1024 * 1024 }
+    }
+}
+
+impl Rule for CachesRule {
+    fn id(&self) -> &str {
+        "caches"
+    }
+
+    fn name(&self) -> &str {
+        "Package-manager and application caches"
+    }
+
+    fn check(&self, _context: &RuleContext) -> Vec<Finding> {
+        let caches = find_caches();
+        caches
+            .iter()
+            .filter(|cache| cache.size >= self.min_size)
+            .map(|cache| Finding {
+                rule: self.id().to_string(),
+                severity: if cache.size > self.warn_size { Severity::Warning } else { Severity::Info },
+                path: Some(cache.path.clone()),
+                message: format!("{} cache in {:?}", cache.name, cache.path),
+                reclaimable: cache.size,
+                action: match &cache.command {
+                    Some(command) => SuggestedAction::RunCommand(command.clone()),
+                    None => SuggestedAction::ClearDirectory(cache.path.clone(), nested_in(&caches, &cache.path)),
+                },
+            })
+            .collect()
+    }
+
+    fn settings_ui(&mut self, ui: &mut egui::Ui) {
+        let mut min_mb = self.min_size / (1024 * 1024);
+        let mut warn_mb = self.warn_size / (1024 * 1024);
+        ui.horizontal(|ui| {
+            ui.label("Ignore caches smaller than");
+            ui.add(egui::DragValue::new(&mut min_mb));
+            ui.label("MB");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Warn above");
+            ui.add(egui::DragValue::new(&mut warn_mb));
+            ui.label("MB");
+        });
+        self.min_size = min_mb * 1024 * 1024;
+        self.warn_size = warn_mb * 1024 * 1024;
+    }
+}
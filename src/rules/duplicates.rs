@@ -0,0 +1,61 @@
+use super::{Finding, Rule, RuleContext, Severity, SuggestedAction};
+use crate::duplicate_finder::find_duplicates;
+
+//groups of identical files under the scanned path
+pub struct DuplicatesRule {
+    //groups wasting less than this many bytes are not reported
+    pub min_wasted: u64,
+    //groups wasting more than this are warnings instead of info
+    pub warn_wasted: u64,
+}
+
+impl Default for DuplicatesRule {
+    fn default() -> Self {
+        Self { min_wasted: 0, warn_wasted: 100 * 1024 * 1024 }
+    }
+}
+
+impl Rule for DuplicatesRule {
+    fn id(&self) -> &str {
+        "duplicates"
+    }
+
+    fn name(&self) -> &str {
+        "Duplicate files"
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<Finding> {
+        find_duplicates(context.root, |_, _, _| {})
+            .into_iter()
+            .filter(|group| group.wasted() >= self.min_wasted)
+            .map(|group| {
+                let paths: Vec<String> = group.paths.iter().map(|path| format!("{:?}", path)).collect();
+                Finding {
+                    rule: self.id().to_string(),
+                    severity: if group.wasted() > self.warn_wasted { Severity::Warning } else { Severity::Info },
+                    path: group.paths.first().cloned(),
+                    message: format!("Duplicates: {}", paths.join(", ")),
+                    reclaimable: group.wasted(),
+                    action: SuggestedAction::Review,
+                }
+            })
+            .collect()
+    }
+
+    fn settings_ui(&mut self, ui: &mut egui::Ui) {
+        let mut min_mb = self.min_wasted / (1024 * 1024);
+        let mut warn_mb = self.warn_wasted / (1024 * 1024);
+        ui.horizontal(|ui| {
+            ui.label("Ignore groups wasting less than");
+            ui.add(egui::DragValue::new(&mut min_mb));
+            ui.label("MB");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Warn above");
+            ui.add(egui::DragValue::new(&mut warn_mb));
+            ui.label("MB");
+        });
+        self.min_wasted = min_mb * 1024 * 1024;
+        self.warn_wasted = warn_mb * 1024 * 1024;
+    }
+}
@@ -0,0 +1,213 @@
+use std::path::{Path, PathBuf};
+
+mod build_artifacts;
+mod caches;
+mod duplicates;
+mod snap_flatpak;
+mod stale;
+mod system;
+mod trash;
+pub use build_artifacts::BuildArtifactsRule;
+pub use caches::CachesRule;
+pub use duplicates::DuplicatesRule;
+pub use snap_flatpak::SnapFlatpakRule;
+pub use stale::StaleDirectoriesRule;
+pub use system::{CrashDumpsRule, JournalRule, OldKernelsRule, RotatedLogsRule, TempFilesRule};
+pub use trash::TrashRule;
+
+//user rules look at the user's own files, system rules at what an admin cleans up
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Category {
+    User,
+    System,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Severity {
+    Info,
+    Warning,
+}
+
+//what the user could do about a finding
+#[derive(Clone, PartialEq, Debug)]
+pub enum SuggestedAction {
+    Review,
+    EmptyTrash,
+    MoveToTrash(PathBuf),
+    Delete(Vec<PathBuf>),
+    //empty a directory but keep it and the listed paths inside it
+    ClearDirectory(PathBuf, Vec<PathBuf>),
+    RunCommand(String),
+}
+
+impl SuggestedAction {
+    //what carrying it out would remove or empty
+    pub fn paths(&self) -> Vec<&Path> {
+        match self {
+            SuggestedAction::MoveToTrash(path) | SuggestedAction::ClearDirectory(path, _) => vec![path.as_path()],
+            SuggestedAction::Delete(paths) => paths.iter().map(|path| path.as_path()).collect(),
+            SuggestedAction::Review | SuggestedAction::EmptyTrash | SuggestedAction::RunCommand(_) => Vec::new(),
+        }
+    }
+
+    //whether it can be carried out from here: reviews need a person, sudo commands a terminal
+    pub fn can_apply(&self) -> bool {
+        match self {
+            SuggestedAction::Review => false,
+            SuggestedAction::RunCommand(command) => !command.starts_with("sudo "),
+            _ => true,
+        }
+    }
+
+    pub fn apply(&self) -> Result<(), std::io::Error> {
+        match self {
+            SuggestedAction::Review => Ok(()),
+            SuggestedAction::EmptyTrash => {
+                for item in crate::trash::list_items() {
+                    crate::trash::purge(&item)?;
+                }
+                Ok(())
+            }
+            SuggestedAction::MoveToTrash(path) => crate::trash::move_to_trash(path).map(|_| ()),
+            SuggestedAction::Delete(paths) => {
+                for path in paths {
+                    crate::trash::delete_permanently(path)?;
+                }
+                Ok(())
+            }
+            SuggestedAction::ClearDirectory(path, keep) => crate::caches::clear_directory(path, keep),
+            SuggestedAction::RunCommand(command) => crate::caches::run_command(command),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            SuggestedAction::Review => "Review".to_string(),
+            SuggestedAction::EmptyTrash => "Empty the trash".to_string(),
+            SuggestedAction::MoveToTrash(path) => format!("Move {:?} to trash", path),
+            SuggestedAction::Delete(paths) if paths.len() == 1 => format!("Delete {:?}", paths[0]),
+            SuggestedAction::Delete(paths) => format!("Delete {} directories", paths.len()),
+            SuggestedAction::ClearDirectory(path, _) => format!("Clear {:?}", path),
+            SuggestedAction::RunCommand(command) => format!("Run `{}`", command),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Finding {
+    pub rule: String,
+    pub severity: Severity,
+    //where the finding is, for jumping to it in the chart
+    pub path: Option<PathBuf>,
+    pub message: String,
+    pub reclaimable: u64,
+    pub action: SuggestedAction,
+}
+
+//what a rule gets to look at
+pub struct RuleContext<'a> {
+    pub root: &'a Path,
+    //findings touching protected paths are dropped before anyone sees them
+    pub path_rules: &'a crate::path_rules::PathRules,
+}
+
+//one recommendation check; new ones are added to a RuleSet without touching the scan code
+pub trait Rule: Send {
+    fn id(&self) -> &str;
+    fn name(&self) -> &str;
+    fn category(&self) -> Category {
+        Category::User
+    }
+    fn check(&self, context: &RuleContext) -> Vec<Finding>;
+    //settings shown under the rule in the rules panel, most rules have none
+    fn settings_ui(&mut self, _ui: &mut egui::Ui) {}
+}
+
+pub struct RuleEntry {
+    pub enabled: bool,
+    pub rule: Box<dyn Rule>,
+}
+
+pub struct RuleSet {
+    pub rules: Vec<RuleEntry>,
+    pub run_user: bool,
+    pub run_system: bool,
+}
+
+impl RuleSet {
+    pub fn new() -> Self {
+        Self { rules: Vec::new(), run_user: true, run_system: false }
+    }
+
+    pub fn with_defaults() -> Self {
+        let mut rules = Self::new();
+        rules.add(TrashRule);
+        rules.add(StaleDirectoriesRule::default());
+        rules.add(DuplicatesRule::default());
+        rules.add(BuildArtifactsRule::default());
+        rules.add(CachesRule::default());
+        rules.add(SnapFlatpakRule);
+        rules.add(RotatedLogsRule);
+        rules.add(JournalRule::default());
+        rules.add(CrashDumpsRule);
+        rules.add(TempFilesRule::default());
+        rules.add(OldKernelsRule);
+        rules
+    }
+
+    pub fn add(&mut self, rule: impl Rule + 'static) {
+        self.rules.push(RuleEntry { enabled: true, rule: Box::new(rule) });
+    }
+
+    //findings of every enabled rule, most severe first
+    pub fn run(&self, context: &RuleContext) -> Vec<Finding> {
+        let mut findings: Vec<Finding> = self
+            .rules
+            .iter()
+            .filter(|entry| entry.enabled && self.runs(entry.rule.category()))
+            .flat_map(|entry| entry.rule.check(context))
+            .filter(|finding| {
+                finding.path.iter().map(|path| path.as_path()).chain(finding.action.paths()).all(|path| context.path_rules.protecting(path).is_none())
+            })
+            .collect();
+        findings.sort_by(|a, b| b.severity.cmp(&a.severity).then(b.reclaimable.cmp(&a.reclaimable)));
+        findings
+    }
+
+    pub fn name_of(&self, id: &str) -> String {
+        self.rules
+            .iter()
+            .find(|entry| entry.rule.id() == id)
+            .map(|entry| entry.rule.name().to_string())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    fn runs(&self, category: Category) -> bool {
+        match category {
+            Category::User => self.run_user,
+            Category::System => self.run_system,
+        }
+    }
+
+    //the rules panel: user and system rules in their own groups, a checkbox per rule and its settings
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        for (category, title) in [(Category::User, "User rules"), (Category::System, "System rules")] {
+            let mut run = self.runs(category);
+            ui.checkbox(&mut run, egui::RichText::new(title).strong());
+            match category {
+                Category::User => self.run_user = run,
+                Category::System => self.run_system = run,
+            }
+            ui.indent(title, |ui| {
+                ui.add_enabled_ui(run, |ui| {
+                    for entry in self.rules.iter_mut().filter(|entry| entry.rule.category() == category) {
+                        ui.checkbox(&mut entry.enabled, entry.rule.name());
+                        ui.indent(entry.rule.id().to_string(), |ui| {
+                            ui.add_enabled_ui(entry.enabled, |ui| entry.rule.settings_ui(ui));
+                        });
+                    }
+                });
+            });
+        }
+    }
+}
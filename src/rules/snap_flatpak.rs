@@ -0,0 +1,181 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use super::{Finding, Rule, RuleContext, Severity, SuggestedAction};
+
+const SNAPD_SNAPS: &str = "/var/lib/snapd/snaps";
+const SYSTEM_FLATPAK: &str = "/var/lib/flatpak";
+
+//revisions of snaps that aren't the current one, and flatpak runtimes no installed app uses
+pub struct SnapFlatpakRule;
+
+impl Rule for SnapFlatpakRule {
+    fn id(&self) -> &str {
+        "snap_flatpak"
+    }
+
+    fn name(&self) -> &str {
+        "Old snap revisions and unused Flatpak runtimes"
+    }
+
+    fn check(&self, _context: &RuleContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        self.disabled_snaps(&mut findings);
+        if let Some(home) = dirs::home_dir() {
+            self.old_snap_data(&home.join("snap"), &mut findings);
+            self.unused_runtimes(&home.join(".local/share/flatpak"), true, &mut findings);
+        }
+        self.unused_runtimes(Path::new(SYSTEM_FLATPAK), false, &mut findings);
+        findings
+    }
+}
+
+fn subdirectories(path: &Path) -> Vec<PathBuf> {
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir() && !path.is_symlink())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+//"current" in a snap's directory links to the revision in use
+fn current_revision(snap_dir: &Path) -> Option<String> {
+    fs::read_link(snap_dir.join("current")).ok().map(|target| file_name(&target))
+}
+
+//numbered revisions from the store, x1, x2... for locally installed ones
+fn is_revision(name: &str) -> bool {
+    let digits = name.strip_prefix('x').unwrap_or(name);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+//values of key in one [group] of a flatpak metadata file
+fn metadata_values(metadata: &str, group: &str, key: &str) -> Vec<String> {
+    let mut in_group = false;
+    let mut values = Vec::new();
+    for line in metadata.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_group = line == format!("[{}]", group);
+        } else if in_group {
+            if let Some(value) = line.strip_prefix(key).and_then(|rest| rest.strip_prefix('=')) {
+                values.push(value.to_string());
+            }
+        }
+    }
+    values
+}
+
+impl SnapFlatpakRule {
+    //snapd keeps the previous revisions' squashfs images in /var/lib/snapd/snaps as <name>_<revision>.snap
+    fn disabled_snaps(&self, findings: &mut Vec<Finding>) {
+        let Ok(entries) = fs::read_dir(SNAPD_SNAPS) else { return };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let file = file_name(&entry.path());
+            let Some((name, revision)) = file.strip_suffix(".snap").and_then(|stem| stem.rsplit_once('_')) else { continue };
+            let Some(current) = current_revision(&Path::new("/snap").join(name)) else { continue };
+            if current == revision {
+                continue;
+            }
+            let size = entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+            findings.push(Finding {
+                rule: self.id().to_string(),
+                severity: Severity::Warning,
+                path: Some(entry.path()),
+                message: format!("Disabled revision {} of snap {} (current is {})", revision, name, current),
+                reclaimable: size,
+                action: SuggestedAction::RunCommand(format!("sudo snap remove {} --revision={}", name, revision)),
+            });
+        }
+    }
+
+    //~/snap/<name>/<revision> holds per-revision user data, only the current one is used
+    fn old_snap_data(&self, snap_home: &Path, findings: &mut Vec<Finding>) {
+        for snap_dir in subdirectories(snap_home) {
+            let Some(current) = current_revision(&snap_dir) else { continue };
+            for revision_dir in subdirectories(&snap_dir) {
+                let revision = file_name(&revision_dir);
+                if !is_revision(&revision) || revision == current {
+                    continue;
+                }
+                findings.push(Finding {
+                    rule: self.id().to_string(),
+                    severity: Severity::Info,
+                    path: Some(revision_dir.clone()),
+                    message: format!("Data of old revision {} of snap {} in {:?}", revision, file_name(&snap_dir), revision_dir),
+                    reclaimable: crate::directory_size(&revision_dir),
+                    action: SuggestedAction::Delete(vec![revision_dir]),
+                });
+            }
+        }
+    }
+
+    //a runtime is in use when an app names it as runtime= or sdk=, or when it extends an installed app or a runtime in use
+    fn unused_runtimes(&self, installation: &Path, user: bool, findings: &mut Vec<Finding>) {
+        let mut used = HashSet::new();
+        for app in subdirectories(&installation.join("app")) {
+            for arch in subdirectories(&app) {
+                for branch in subdirectories(&arch) {
+                    //app extensions (Locale, Debug...) sit under runtime/ but name the app as app/<name>/<arch>/<branch>
+                    used.insert(format!("app/{}/{}/{}", file_name(&app), file_name(&arch), file_name(&branch)));
+                    let Ok(metadata) = fs::read_to_string(branch.join("active/metadata")) else { continue };
+                    used.extend(metadata_values(&metadata, "Application", "runtime"));
+                    used.extend(metadata_values(&metadata, "Application", "sdk"));
+                }
+            }
+        }
+
+        let mut runtimes = Vec::new();
+        for runtime in subdirectories(&installation.join("runtime")) {
+            for arch in subdirectories(&runtime) {
+                for branch in subdirectories(&arch) {
+                    let reference = format!("{}/{}/{}", file_name(&runtime), file_name(&arch), file_name(&branch));
+                    let metadata = fs::read_to_string(branch.join("active/metadata")).unwrap_or_default();
+                    let extension_of: Vec<String> = metadata_values(&metadata, "ExtensionOf", "ref")
+                        .into_iter()
+                        .map(|extended| extended.trim_start_matches("runtime/").to_string())
+                        .collect();
+                    runtimes.push((reference, extension_of, branch));
+                }
+            }
+        }
+        //extensions of extensions (GL drivers of a used platform...) need a few passes to settle
+        loop {
+            let before = used.len();
+            for (reference, extension_of, _) in &runtimes {
+                if extension_of.iter().any(|extended| used.contains(extended)) {
+                    used.insert(reference.clone());
+                }
+            }
+            if used.len() == before {
+                break;
+            }
+        }
+
+        for (reference, _, branch) in runtimes {
+            if used.contains(&reference) {
+                continue;
+            }
+            findings.push(Finding {
+                rule: self.id().to_string(),
+                severity: Severity::Warning,
+                path: Some(branch.clone()),
+                message: format!("Flatpak runtime {} is not used by any installed app", reference),
+                reclaimable: crate::directory_size(&branch),
+                action: SuggestedAction::RunCommand(format!(
+                    "flatpak uninstall {}--noninteractive runtime/{}",
+                    if user { "--user " } else { "" },
+                    reference
+                )),
+            });
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+use super::{Finding, Rule, RuleContext, Severity, SuggestedAction};
+
+//directories under the scanned path where nothing has been used for a while;
+//only the topmost stale directory of a stale subtree is reported
+pub struct StaleDirectoriesRule {
+    pub days: u64,
+    //smaller stale directories are left out of the report
+    pub min_size: u64,
+    //access times are ignored anyway on noatime mounts, where they never change
+    pub use_atime: bool,
+}
+
+impl Default for StaleDirectoriesRule {
+    fn default() -> Self {
+        Self { days: 30, min_size: 1024 * 1024, use_atime: true }
+    }
+}
+
+impl Rule for StaleDirectoriesRule {
+    fn id(&self) -> &str {
+        "stale"
+    }
+
+    fn name(&self) -> &str {
+        "Directories not used recently"
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<Finding> {
+        let cutoff = SystemTime::now() - Duration::from_secs(self.days * 24 * 60 * 60);
+        let use_atime = self.use_atime && !crate::mounts::mount_of(context.root).map(|mount| mount.has_option("noatime")).unwrap_or(false);
+        let recently_used = recently_used_files();
+
+        //size and newest use of everything below each directory
+        let mut usage: HashMap<PathBuf, (u64, SystemTime)> = HashMap::new();
+        for entry in WalkDir::new(context.root).into_iter().filter_map(|e| e.ok()) {
+            //only regular files count: directory access times change whenever anything lists them, a scan included
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else { continue };
+            let mut last_used = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            if use_atime {
+                if let Ok(accessed) = metadata.accessed() {
+                    last_used = last_used.max(accessed);
+                }
+            }
+            if let Some(used) = recently_used.get(entry.path()) {
+                last_used = last_used.max(*used);
+            }
+            for dir in entry.path().ancestors().skip(1).take_while(|dir| dir.starts_with(context.root)) {
+                let totals = usage.entry(dir.to_path_buf()).or_insert((0, SystemTime::UNIX_EPOCH));
+                totals.0 += metadata.len();
+                totals.1 = totals.1.max(last_used);
+            }
+        }
+
+        let is_stale = |dir: &Path| usage.get(dir).map(|(_, last_used)| *last_used < cutoff).unwrap_or(false);
+        let mut findings: Vec<Finding> = usage
+            .iter()
+            .filter(|(dir, (size, _))| {
+                dir.as_path() != context.root
+                    && *size >= self.min_size
+                    && is_stale(dir)
+                    && !dir.parent().map(|parent| parent != context.root && is_stale(parent)).unwrap_or(false)
+            })
+            .map(|(dir, (size, last_used))| Finding {
+                rule: self.id().to_string(),
+                severity: Severity::Info,
+                path: Some(dir.clone()),
+                message: format!("{:?} last used {} ({})", dir, crate::format_date(*last_used), crate::format_age(*last_used)),
+                reclaimable: *size,
+                action: SuggestedAction::MoveToTrash(dir.clone()),
+            })
+            .collect();
+        findings.sort_by_key(|finding| std::cmp::Reverse(finding.reclaimable));
+        findings
+    }
+
+    fn settings_ui(&mut self, ui: &mut egui::Ui) {
+        let mut min_mb = self.min_size / (1024 * 1024);
+        ui.horizontal(|ui| {
+            ui.label("Not used for");
+            ui.add(egui::DragValue::new(&mut self.days).clamp_range(1..=3650));
+            ui.label("days");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Ignore directories smaller than");
+            ui.add(egui::DragValue::new(&mut min_mb));
+            ui.label("MB");
+        });
+        ui.checkbox(&mut self.use_atime, "Use access times (not on noatime mounts)");
+        self.min_size = min_mb * 1024 * 1024;
+    }
+}
+
+//when desktop applications last opened each file, from ~/.local/share/recently-used.xbel
+fn recently_used_files() -> HashMap<PathBuf, SystemTime> {
+    let mut used = HashMap::new();
+    let Some(xbel) = dirs::data_dir().map(|data| data.join("recently-used.xbel")) else { return used };
+    let Ok(contents) = fs::read_to_string(xbel) else { return used };
+    for bookmark in contents.split("<bookmark ").skip(1) {
+        let Some(href) = attribute(bookmark, "href") else { continue };
+        let Some(path) = href.strip_prefix("file://") else { continue };
+        let newest = ["added", "modified", "visited"]
+            .iter()
+            .filter_map(|name| attribute(bookmark, name))
+            .filter_map(parse_utc)
+            .max();
+        if let Some(newest) = newest {
+            used.insert(crate::trash::decode_path(path), newest);
+        }
+    }
+    used
+}
+
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let start = element.find(&format!(" {}=\"", name)).or_else(|| element.starts_with(&format!("{}=\"", name)).then_some(0))?;
+    let value = &element[start..];
+    let value = &value[value.find('"')? + 1..];
+    Some(&value[..value.find('"')?])
+}
+
+//xbel dates look like 2023-10-01T12:30:00.123456Z
+fn parse_utc(date: &str) -> Option<SystemTime> {
+    let (day, time) = date.trim_end_matches('Z').split_once('T')?;
+    let time = time.split('.').next()?;
+    let day: Vec<i32> = day.split('-').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let time: Vec<i32> = time.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    if day.len() != 3 || time.len() != 3 {
+        return None;
+    }
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    tm.tm_year = day[0] - 1900;
+    tm.tm_mon = day[1] - 1;
+    tm.tm_mday = day[2];
+    tm.tm_hour = time[0];
+    tm.tm_min = time[1];
+    tm.tm_sec = time[2];
+    let seconds = unsafe { libc::timegm(&mut tm) };
+    (seconds >= 0).then(|| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds as u64))
+}
@@ -0,0 +1,364 @@
+use std::ffi::CStr;
+use std::fs;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+use super::{Category, Finding, Rule, RuleContext, Severity, SuggestedAction};
+
+const LOG_DIR: &str = "/var/log";
+const JOURNAL_DIRS: [&str; 2] = ["/var/log/journal", "/run/log/journal"];
+const CRASH_DIRS: [&str; 2] = ["/var/crash", "/var/lib/systemd/coredump"];
+const TEMP_DIRS: [&str; 2] = ["/tmp", "/var/tmp"];
+//sockets, locks and per-service private dirs that live in /tmp as long as the system or a session is up
+const TEMP_KEEP: [&str; 9] = ["systemd-private-*", ".X11-unix", ".ICE-unix", ".font-unix", ".XIM-unix", ".Test-unix", ".X*-lock", "tmux-*", "ssh-*"];
+
+//old mtimes don't make runtime state stale: a socket, fifo or lock file means a live server may still need it
+fn holds_runtime_state(path: &Path) -> bool {
+    WalkDir::new(path).into_iter().filter_map(|e| e.ok()).any(|entry| {
+        let file_type = entry.file_type();
+        let name = entry.file_name().to_string_lossy();
+        file_type.is_socket() || file_type.is_fifo() || name.ends_with(".lock") || name.ends_with("-lock") || name.ends_with(".pid")
+    })
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::symlink_metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn age(time: Option<SystemTime>) -> String {
+    time.map(crate::format_age).unwrap_or_else(|| "unknown".to_string())
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::symlink_metadata(path).map(|metadata| metadata.len()).unwrap_or(0)
+}
+
+//rotated logs are compressed, numbered (syslog.1) or dated (messages-20231001) copies of a log
+fn is_rotated_log(name: &str) -> bool {
+    let compressed = [".gz", ".xz", ".bz2", ".zst", ".old"].iter().any(|suffix| name.ends_with(suffix));
+    let numbered = name.rsplit_once('.').map(|(_, last)| !last.is_empty() && last.chars().all(|c| c.is_ascii_digit())).unwrap_or(false);
+    let dated = name.rsplit_once('-').map(|(_, last)| last.len() == 8 && last.chars().all(|c| c.is_ascii_digit())).unwrap_or(false);
+    compressed || numbered || dated
+}
+
+pub struct RotatedLogsRule;
+
+impl Rule for RotatedLogsRule {
+    fn id(&self) -> &str {
+        "rotated_logs"
+    }
+
+    fn name(&self) -> &str {
+        "Rotated log archives in /var/log"
+    }
+
+    fn category(&self) -> Category {
+        Category::System
+    }
+
+    fn check(&self, _context: &RuleContext) -> Vec<Finding> {
+        let mut paths = Vec::new();
+        let mut size = 0;
+        let mut oldest: Option<SystemTime> = None;
+        let walker = WalkDir::new(LOG_DIR).into_iter().filter_entry(|entry| !JOURNAL_DIRS.contains(&entry.path().to_str().unwrap_or_default()));
+        for entry in walker.filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() || !is_rotated_log(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            if let Ok(metadata) = entry.metadata() {
+                size += metadata.len();
+                if let Ok(time) = metadata.modified() {
+                    oldest = Some(oldest.map_or(time, |oldest| oldest.min(time)));
+                }
+            }
+            paths.push(entry.into_path());
+        }
+        if paths.is_empty() {
+            return Vec::new();
+        }
+        vec![Finding {
+            rule: self.id().to_string(),
+            severity: Severity::Info,
+            path: Some(PathBuf::from(LOG_DIR)),
+            message: format!("{} rotated log archives in {}, oldest from {}", paths.len(), LOG_DIR, age(oldest)),
+            reclaimable: size,
+            action: SuggestedAction::Delete(paths),
+        }]
+    }
+}
+
+//the systemd journal, reported when it is bigger than max_size
+pub struct JournalRule {
+    pub max_size: u64,
+}
+
+impl Default for JournalRule {
+    fn default() -> Self {
+        Self { max_size: 500 * 1024 * 1024 }
+    }
+}
+
+impl Rule for JournalRule {
+    fn id(&self) -> &str {
+        "journal"
+    }
+
+    fn name(&self) -> &str {
+        "Large systemd journal"
+    }
+
+    fn category(&self) -> Category {
+        Category::System
+    }
+
+    fn check(&self, _context: &RuleContext) -> Vec<Finding> {
+        let mut size = 0;
+        let mut oldest: Option<SystemTime> = None;
+        for dir in JOURNAL_DIRS {
+            for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
+                if entry.file_type().is_file() {
+                    let time = modified(entry.path());
+                    size += file_size(entry.path());
+                    oldest = match (oldest, time) {
+                        (Some(oldest), Some(time)) => Some(oldest.min(time)),
+                        (oldest, time) => oldest.or(time),
+                    };
+                }
+            }
+        }
+        if size <= self.max_size {
+            return Vec::new();
+        }
+        vec![Finding {
+            rule: self.id().to_string(),
+            severity: Severity::Warning,
+            path: JOURNAL_DIRS.iter().map(PathBuf::from).find(|dir| dir.is_dir()),
+            message: format!("The systemd journal uses {}, oldest entries from {}", crate::format_size(size as f64), age(oldest)),
+            reclaimable: size - self.max_size,
+            action: SuggestedAction::RunCommand(format!("sudo journalctl --vacuum-size={}M", self.max_size / (1024 * 1024))),
+        }]
+    }
+
+    fn settings_ui(&mut self, ui: &mut egui::Ui) {
+        let mut max_mb = self.max_size / (1024 * 1024);
+        ui.horizontal(|ui| {
+            ui.label("Keep the journal under");
+            ui.add(egui::DragValue::new(&mut max_mb).clamp_range(16..=1024 * 1024));
+            ui.label("MB");
+        });
+        self.max_size = max_mb * 1024 * 1024;
+    }
+}
+
+pub struct CrashDumpsRule;
+
+impl Rule for CrashDumpsRule {
+    fn id(&self) -> &str {
+        "crash_dumps"
+    }
+
+    fn name(&self) -> &str {
+        "Crash reports and core dumps"
+    }
+
+    fn category(&self) -> Category {
+        Category::System
+    }
+
+    fn check(&self, _context: &RuleContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for dir in CRASH_DIRS {
+            let Ok(entries) = fs::read_dir(dir) else { continue };
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                findings.push(Finding {
+                    rule: self.id().to_string(),
+                    severity: Severity::Warning,
+                    path: Some(path.clone()),
+                    message: format!("Crash dump {:?} from {}", path, age(modified(&path))),
+                    reclaimable: crate::directory_size(&path),
+                    action: SuggestedAction::Delete(vec![path]),
+                });
+            }
+        }
+        findings
+    }
+}
+
+//entries of /tmp and /var/tmp where nothing inside changed for days
+pub struct TempFilesRule {
+    pub days: u64,
+}
+
+impl Default for TempFilesRule {
+    fn default() -> Self {
+        Self { days: 10 }
+    }
+}
+
+impl Rule for TempFilesRule {
+    fn id(&self) -> &str {
+        "temp_files"
+    }
+
+    fn name(&self) -> &str {
+        "Stale temporary files"
+    }
+
+    fn category(&self) -> Category {
+        Category::System
+    }
+
+    fn check(&self, _context: &RuleContext) -> Vec<Finding> {
+        let cutoff = SystemTime::now() - Duration::from_secs(self.days * 24 * 60 * 60);
+        let mut findings = Vec::new();
+        let processes = crate::open_files::processes();
+        //something a process has open or runs in is in use however old it looks
+        let in_use = |path: &Path| {
+            processes.iter().any(|process| {
+                process.cwd.as_deref().map(|cwd| cwd.starts_with(path)).unwrap_or(false) || process.fds.iter().any(|(_, target)| target.starts_with(path))
+            })
+        };
+        for dir in TEMP_DIRS {
+            let Ok(entries) = fs::read_dir(dir) else { continue };
+            for entry in entries.filter_map(|e| e.ok()) {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if TEMP_KEEP.iter().any(|pattern| crate::filters::glob_match(pattern, &name)) {
+                    continue;
+                }
+                let path = entry.path();
+                let newest = WalkDir::new(&path).into_iter().filter_map(|e| e.ok()).filter_map(|e| modified(e.path())).max();
+                if newest.map(|newest| newest >= cutoff).unwrap_or(true) {
+                    continue;
+                }
+                if holds_runtime_state(&path) || in_use(&path) {
+                    continue;
+                }
+                findings.push(Finding {
+                    rule: self.id().to_string(),
+                    severity: Severity::Info,
+                    path: Some(path.clone()),
+                    message: format!("Temporary {:?} last changed {}", path, age(newest)),
+                    reclaimable: crate::directory_size(&path),
+                    action: SuggestedAction::Delete(vec![path]),
+                });
+            }
+        }
+        findings
+    }
+
+    fn settings_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Unchanged for");
+            ui.add(egui::DragValue::new(&mut self.days).clamp_range(1..=3650));
+            ui.label("days");
+        });
+    }
+}
+
+//kernels other than the running one and the newest installed one, which is kept as a fallback
+pub struct OldKernelsRule;
+
+fn running_kernel() -> Option<String> {
+    let mut name: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut name) } != 0 {
+        return None;
+    }
+    let release = unsafe { CStr::from_ptr(name.release.as_ptr()) };
+    Some(release.to_string_lossy().to_string())
+}
+
+//compares versions like 6.5.0-9-generic by their numeric parts
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
+
+fn boot_files(version: &str) -> Vec<PathBuf> {
+    ["vmlinuz-", "initrd.img-", "System.map-", "config-"]
+        .iter()
+        .map(|prefix| PathBuf::from(format!("/boot/{}{}", prefix, version)))
+        .chain(std::iter::once(PathBuf::from(format!("/boot/initramfs-{}.img", version))))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+//kernels are owned by the package manager, so removing them goes through it
+fn remove_command(version: &str) -> SuggestedAction {
+    if Path::new("/var/lib/dpkg").is_dir() {
+        SuggestedAction::RunCommand(format!("sudo apt-get remove linux-image-{}", version))
+    } else if Path::new("/usr/bin/dnf").exists() {
+        SuggestedAction::RunCommand(format!("sudo dnf remove kernel-core-{}", version))
+    } else {
+        SuggestedAction::Review
+    }
+}
+
+impl Rule for OldKernelsRule {
+    fn id(&self) -> &str {
+        "old_kernels"
+    }
+
+    fn name(&self) -> &str {
+        "Old kernels in /boot and /lib/modules"
+    }
+
+    fn category(&self) -> Category {
+        Category::System
+    }
+
+    fn check(&self, _context: &RuleContext) -> Vec<Finding> {
+        let Ok(entries) = fs::read_dir("/lib/modules") else { return Vec::new() };
+        let mut versions: Vec<String> = entries.filter_map(|e| e.ok()).map(|entry| entry.file_name().to_string_lossy().to_string()).collect();
+        versions.sort_by_key(|version| version_key(version));
+        let newest = versions.last().cloned();
+        let running = running_kernel();
+        versions
+            .into_iter()
+            .filter(|version| Some(version) != newest.as_ref() && Some(version) != running.as_ref())
+            .map(|version| {
+                let modules = Path::new("/lib/modules").join(&version);
+                let size = crate::directory_size(&modules) + boot_files(&version).iter().map(|path| file_size(path)).sum::<u64>();
+                Finding {
+                    rule: self.id().to_string(),
+                    severity: Severity::Info,
+                    path: Some(modules.clone()),
+                    message: format!("Kernel {} is neither running nor the newest, installed {}", version, age(modified(&modules))),
+                    reclaimable: size,
+                    action: remove_command(&version),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_rotated_log, version_key};
+
+    #[test]
+    fn rotated_logs_are_compressed_numbered_or_dated() {
+        assert!(is_rotated_log("syslog.1"));
+        assert!(is_rotated_log("syslog.2.gz"));
+        assert!(is_rotated_log("kern.log.old"));
+        assert!(is_rotated_log("messages-20240301"));
+        assert!(is_rotated_log("journal.zst"));
+        assert!(!is_rotated_log("syslog"));
+        assert!(!is_rotated_log("dpkg.log"));
+        assert!(!is_rotated_log("syslog."));
+        assert!(!is_rotated_log("messages-2024"));
+    }
+
+    #[test]
+    fn kernel_versions_sort_numerically() {
+        assert_eq!(version_key("6.5.0-14-generic"), vec![6, 5, 0, 14]);
+        assert_eq!(version_key("6.8.9-300.fc40.x86_64"), vec![6, 8, 9, 300, 40, 86, 64]);
+        let mut versions = vec!["6.10.0-1", "6.9.12-3", "6.9.2-7"];
+        versions.sort_by_key(|version| version_key(version));
+        assert_eq!(versions, vec!["6.9.2-7", "6.9.12-3", "6.10.0-1"]);
+    }
+}
@@ -0,0 +1,29 @@
+use super::{Finding, Rule, RuleContext, Severity, SuggestedAction};
+
+pub struct TrashRule;
+
+impl Rule for TrashRule {
+    fn id(&self) -> &str {
+        "trash"
+    }
+
+    fn name(&self) -> &str {
+        "Non-empty trash"
+    }
+
+    fn check(&self, _context: &RuleContext) -> Vec<Finding> {
+        let items = crate::trash::list_items();
+        if items.is_empty() {
+            return Vec::new();
+        }
+        let size: f64 = items.iter().map(|item| item.size).sum();
+        vec![Finding {
+            rule: self.id().to_string(),
+            severity: Severity::Info,
+            path: Some(crate::trash::home_trash()),
+            message: format!("Empty the trash ({} items)", items.len()),
+            reclaimable: size as u64,
+            action: SuggestedAction::EmptyTrash,
+        }]
+    }
+}
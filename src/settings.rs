@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use crate::filters::FilterSet;
+use crate::path_rules::PathRules;
+
+const KEY: &str = "settings";
+
+//what is kept between runs in eframe's storage; the window geometry is kept by eframe itself
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub path: String,
+    pub filters: FilterSet,
+    pub path_rules: PathRules,
+    pub sorted: bool,
+    pub whole_disk: bool,
+    pub dark_mode: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            path: "/home".to_owned(),
+            filters: FilterSet::default(),
+            path_rules: PathRules::default(),
+            sorted: false,
+            whole_disk: false,
+            dark_mode: true,
+        }
+    }
+}
+
+pub fn load(storage: Option<&dyn eframe::Storage>) -> Settings {
+    storage.and_then(|storage| eframe::get_value(storage, KEY)).unwrap_or_default()
+}
+
+pub fn save(storage: &mut dyn eframe::Storage, settings: &Settings) {
+    eframe::set_value(storage, KEY, settings);
+}
@@ -0,0 +1,256 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use egui::plot::{Legend, Line, Plot, PlotPoints};
+
+const INTERVAL: Duration = Duration::from_secs(1);
+//one minute of samples
+const HISTORY: usize = 60;
+//diskstats counts sectors of 512 bytes whatever the device's real sector size
+const SECTOR: f64 = 512.0;
+
+//cumulative counters of one device from /proc/diskstats
+#[derive(Clone, Copy)]
+struct DiskStats {
+    reads: u64,
+    sectors_read: u64,
+    writes: u64,
+    sectors_written: u64,
+    //milliseconds the device had requests in flight
+    io_ticks: u64,
+}
+
+//whole devices only: partitions are in /proc/diskstats too but have no /sys/block entry
+fn read_diskstats() -> HashMap<String, DiskStats> {
+    let Ok(contents) = fs::read_to_string("/proc/diskstats") else { return HashMap::new() };
+    contents
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 13 || !Path::new("/sys/block").join(fields[2]).exists() {
+                return None;
+            }
+            let field = |i: usize| fields[i].parse::<u64>().ok();
+            let stats = DiskStats { reads: field(3)?, sectors_read: field(5)?, writes: field(7)?, sectors_written: field(9)?, io_ticks: field(12)? };
+            Some((fields[2].to_string(), stats))
+        })
+        .collect()
+}
+
+//bytes this process made the disks read and write, from /proc/self/io; page cache hits aren't counted
+#[derive(Clone, Copy, Default)]
+pub struct SelfIo {
+    read_bytes: u64,
+    write_bytes: u64,
+}
+
+impl SelfIo {
+    pub fn read() -> Self {
+        let contents = fs::read_to_string("/proc/self/io").unwrap_or_default();
+        let value = |key: &str| {
+            contents
+                .lines()
+                .find_map(|line| line.strip_prefix(key).and_then(|rest| rest.strip_prefix(": ")))
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(0)
+        };
+        Self { read_bytes: value("read_bytes"), write_bytes: value("write_bytes") }
+    }
+}
+
+//what the last scan cost the disks
+pub struct ScanImpact {
+    pub path: String,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub elapsed: Duration,
+}
+
+//started before a scan, finished after it
+pub struct ScanMeter {
+    start: Instant,
+    io: SelfIo,
+}
+
+impl ScanMeter {
+    pub fn start() -> Self {
+        Self { start: Instant::now(), io: SelfIo::read() }
+    }
+
+    pub fn finish(self, path: &str) -> ScanImpact {
+        let io = SelfIo::read();
+        ScanImpact {
+            path: path.to_string(),
+            read_bytes: io.read_bytes.saturating_sub(self.io.read_bytes),
+            write_bytes: io.write_bytes.saturating_sub(self.io.write_bytes),
+            elapsed: self.start.elapsed(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Metric {
+    Bandwidth,
+    Iops,
+    Utilization,
+}
+
+//rates of one device, newest sample last
+#[derive(Default)]
+struct DeviceHistory {
+    read: VecDeque<f64>,
+    write: VecDeque<f64>,
+    read_iops: VecDeque<f64>,
+    write_iops: VecDeque<f64>,
+    utilization: VecDeque<f64>,
+}
+
+fn push(history: &mut VecDeque<f64>, value: f64) {
+    history.push_back(value);
+    if history.len() > HISTORY {
+        history.pop_front();
+    }
+}
+
+pub struct ThroughputMonitor {
+    last_sample: Option<(Instant, HashMap<String, DiskStats>, SelfIo)>,
+    devices: BTreeMap<String, DeviceHistory>,
+    //this app's own reads and writes, to compare against the devices
+    own: DeviceHistory,
+    metric: Metric,
+    pub last_scan: Option<ScanImpact>,
+}
+
+impl Default for ThroughputMonitor {
+    fn default() -> Self {
+        Self { last_sample: None, devices: BTreeMap::new(), own: DeviceHistory::default(), metric: Metric::Bandwidth, last_scan: None }
+    }
+}
+
+impl ThroughputMonitor {
+    //drops the history, so closing and reopening the panel doesn't average over the time it was closed
+    pub fn reset(&mut self) {
+        self.last_sample = None;
+        self.devices.clear();
+        self.own = DeviceHistory::default();
+    }
+
+    fn sample(&mut self) {
+        let now = Instant::now();
+        if matches!(&self.last_sample, Some((last, _, _)) if now - *last < INTERVAL) {
+            return;
+        }
+        let stats = read_diskstats();
+        let own = SelfIo::read();
+        if let Some((last, previous, previous_own)) = &self.last_sample {
+            let seconds = (now - *last).as_secs_f64();
+            for (name, current) in &stats {
+                let Some(previous) = previous.get(name) else { continue };
+                //devices that never did anything (unused loop devices, zram...) only clutter the plot
+                if current.reads + current.writes == 0 {
+                    continue;
+                }
+                let rate = |current: u64, previous: u64| current.saturating_sub(previous) as f64 / seconds;
+                let history = self.devices.entry(name.clone()).or_default();
+                push(&mut history.read, rate(current.sectors_read, previous.sectors_read) * SECTOR);
+                push(&mut history.write, rate(current.sectors_written, previous.sectors_written) * SECTOR);
+                push(&mut history.read_iops, rate(current.reads, previous.reads));
+                push(&mut history.write_iops, rate(current.writes, previous.writes));
+                push(&mut history.utilization, (rate(current.io_ticks, previous.io_ticks) / 10.0).min(100.0));
+            }
+            push(&mut self.own.read, own.read_bytes.saturating_sub(previous_own.read_bytes) as f64 / seconds);
+            push(&mut self.own.write, own.write_bytes.saturating_sub(previous_own.write_bytes) as f64 / seconds);
+        }
+        self.last_sample = Some((now, stats, own));
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        self.sample();
+        ui.ctx().request_repaint_after(INTERVAL);
+
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.metric, Metric::Bandwidth, "Bandwidth");
+            ui.selectable_value(&mut self.metric, Metric::Iops, "IOPS");
+            ui.selectable_value(&mut self.metric, Metric::Utilization, "Utilization");
+        });
+        let points = |values: &VecDeque<f64>| {
+            let start = HISTORY - values.len();
+            PlotPoints::from_iter(values.iter().enumerate().map(|(i, value)| [(start + i) as f64 - HISTORY as f64, *value]))
+        };
+        let metric = self.metric;
+        let plot = Plot::new("throughput")
+            .height(250.0)
+            .legend(Legend::default())
+            .allow_boxed_zoom(false)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .include_x(-(HISTORY as f64))
+            .include_x(0.0)
+            .include_y(0.0)
+            .label_formatter(move |name, point| match metric {
+                Metric::Bandwidth => format!("{}\n{}/s", name, crate::format_size(point.y)),
+                Metric::Iops => format!("{}\n{:.0} IOPS", name, point.y),
+                Metric::Utilization => format!("{}\n{:.0}% busy", name, point.y),
+            });
+        let plot = if metric == Metric::Utilization { plot.include_y(100.0) } else { plot };
+        plot.show(ui, |plot_ui| {
+            for (name, history) in &self.devices {
+                match metric {
+                    Metric::Bandwidth => {
+                        plot_ui.line(Line::new(points(&history.read)).name(format!("{} read", name)));
+                        plot_ui.line(Line::new(points(&history.write)).name(format!("{} write", name)));
+                    }
+                    Metric::Iops => {
+                        plot_ui.line(Line::new(points(&history.read_iops)).name(format!("{} reads", name)));
+                        plot_ui.line(Line::new(points(&history.write_iops)).name(format!("{} writes", name)));
+                    }
+                    Metric::Utilization => plot_ui.line(Line::new(points(&history.utilization)).name(name)),
+                }
+            }
+            if metric == Metric::Bandwidth {
+                plot_ui.line(Line::new(points(&self.own.read)).name("this app read"));
+                plot_ui.line(Line::new(points(&self.own.write)).name("this app write"));
+            }
+        });
+
+        ui.separator();
+        egui::Grid::new("throughput_now").striped(true).show(ui, |ui| {
+            for heading in ["Device", "Read", "Write", "IOPS", "Busy"] {
+                ui.strong(heading);
+            }
+            ui.end_row();
+            let last = |values: &VecDeque<f64>| values.back().copied().unwrap_or(0.0);
+            for (name, history) in &self.devices {
+                ui.label(name);
+                ui.label(format!("{}/s", crate::format_size(last(&history.read))));
+                ui.label(format!("{}/s", crate::format_size(last(&history.write))));
+                ui.label(format!("{:.0}", last(&history.read_iops) + last(&history.write_iops)));
+                ui.label(format!("{:.0}%", last(&history.utilization)));
+                ui.end_row();
+            }
+            ui.label("this app");
+            ui.label(format!("{}/s", crate::format_size(last(&self.own.read))));
+            ui.label(format!("{}/s", crate::format_size(last(&self.own.write))));
+            ui.end_row();
+        });
+        match &self.last_scan {
+            Some(scan) => {
+                let seconds = scan.elapsed.as_secs_f64().max(0.001);
+                ui.label(format!(
+                    "Last scan of {} read {} from disk in {:.1} s ({}/s) and wrote {}",
+                    scan.path,
+                    crate::format_size(scan.read_bytes as f64),
+                    seconds,
+                    crate::format_size(scan.read_bytes as f64 / seconds),
+                    crate::format_size(scan.write_bytes as f64)
+                ))
+                .on_hover_text("Files already in the page cache cost no disk reads");
+            }
+            None => {
+                ui.label("No scan measured yet.");
+            }
+        }
+    }
+}
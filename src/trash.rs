@@ -0,0 +1,467 @@
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+//follows the freedesktop.org trash spec: files/ holds the trashed items, info/ a .trashinfo for each
+pub fn home_trash() -> PathBuf {
+    dirs::data_dir()
+        .map(|data| data.join("Trash"))
+        .expect("Could not determine home directory")
+}
+
+//a trashed item as described by its .trashinfo file
+#[derive(Clone)]
+pub struct TrashItem {
+    pub trash_dir: PathBuf,
+    pub name: String,
+    pub original_path: PathBuf,
+    pub deletion_date: String,
+    pub deleted_at: Option<SystemTime>,
+    pub size: f64,
+}
+
+impl TrashItem {
+    pub fn files_path(&self) -> PathBuf {
+        self.trash_dir.join("files").join(&self.name)
+    }
+
+    fn info_path(&self) -> PathBuf {
+        self.trash_dir.join("info").join(format!("{}.trashinfo", self.name))
+    }
+
+    pub fn older_than(&self, days: u64) -> bool {
+        let cutoff = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+        self.deleted_at.map(|deleted| deleted < cutoff).unwrap_or(false)
+    }
+}
+
+//the home trash plus every per-volume trash of this user, with the top directory relative Path= entries start from
+pub fn trash_dirs() -> Vec<(PathBuf, PathBuf)> {
+    let uid = unsafe { libc::getuid() };
+    let mut dirs = vec![(home_trash(), PathBuf::from("/"))];
+    for mount_point in crate::mounts::mount_points() {
+        for trash in [mount_point.join(".Trash").join(uid.to_string()), mount_point.join(format!(".Trash-{}", uid))] {
+            if trash.join("info").is_dir() && !dirs.iter().any(|(dir, _)| dir == &trash) {
+                dirs.push((trash, mount_point.clone()));
+            }
+        }
+    }
+    dirs
+}
+
+pub fn list_items() -> Vec<TrashItem> {
+    let mut items = Vec::new();
+    for (trash_dir, topdir) in trash_dirs() {
+        let Ok(entries) = fs::read_dir(trash_dir.join("info")) else { continue };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let Some(name) = file_name.strip_suffix(".trashinfo") else { continue };
+            match read_trash_info(&entry.path()) {
+                Ok((original, deletion_date)) => {
+                    let mut item = TrashItem {
+                        trash_dir: trash_dir.clone(),
+                        name: name.to_string(),
+                        original_path: topdir.join(original),
+                        deleted_at: parse_deletion_date(&deletion_date),
+                        deletion_date,
+                        size: 0.0,
+                    };
+                    item.size = crate::directory_size(&item.files_path()) as f64;
+                    items.push(item);
+                }
+                Err(e) => eprintln!("Error reading {:?}: {:?}", entry.path(), e),
+            }
+        }
+    }
+    items.sort_by_key(|item| std::cmp::Reverse(item.deleted_at));
+    items
+}
+
+fn read_trash_info(info_path: &Path) -> Result<(PathBuf, String), std::io::Error> {
+    let contents = fs::read_to_string(info_path)?;
+    let mut original = None;
+    let mut deletion_date = String::new();
+    for line in contents.lines() {
+        if let Some(value) = line.strip_prefix("Path=") {
+            original = Some(decode_path(value));
+        } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+            deletion_date = value.to_string();
+        }
+    }
+    let original = original.ok_or(std::io::Error::from(ErrorKind::InvalidData))?;
+    Ok((original, deletion_date))
+}
+
+pub fn purge(item: &TrashItem) -> Result<(), std::io::Error> {
+    let files_path = item.files_path();
+    if files_path.symlink_metadata().is_ok() {
+        delete_permanently(&files_path)?;
+    }
+    fs::remove_file(item.info_path())
+}
+
+pub fn restore(item: &TrashItem) -> Result<(), std::io::Error> {
+    if item.original_path.symlink_metadata().is_ok() {
+        return Err(std::io::Error::from(ErrorKind::AlreadyExists));
+    }
+    if let Some(parent) = item.original_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    move_item(&item.files_path(), &item.original_path)?;
+    fs::remove_file(item.info_path())
+}
+
+pub fn move_to_trash(path: &Path) -> Result<PathBuf, std::io::Error> {
+    //only the parent is resolved, so a symlink is trashed itself instead of the file it points to
+    let name = path.file_name().ok_or(std::io::Error::from(ErrorKind::InvalidInput))?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let path = fs::canonicalize(parent)?.join(name);
+    let (trash, topdir) = trash_for(&path);
+    let files_dir = trash.join("files");
+    let info_dir = trash.join("info");
+    fs::create_dir_all(&files_dir)?;
+    fs::create_dir_all(&info_dir)?;
+
+    let file_name = path
+        .file_name()
+        .ok_or(std::io::Error::from(ErrorKind::InvalidInput))?
+        .to_string_lossy()
+        .to_string();
+    //the info file is created first with create_new so two trashings of the same name can't collide
+    let mut counter = 1;
+    let (trash_name, mut info_file) = loop {
+        let candidate = if counter == 1 {
+            file_name.clone()
+        } else {
+            format!("{}.{}", file_name, counter)
+        };
+        if files_dir.join(&candidate).symlink_metadata().is_err() {
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(info_dir.join(format!("{}.trashinfo", candidate)))
+            {
+                Ok(file) => break (candidate, file),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+        }
+        counter += 1;
+    };
+    let info_path = info_dir.join(format!("{}.trashinfo", trash_name));
+    //per-volume trashes store the path relative to the volume, so it still works when mounted elsewhere
+    let stored_path = match &topdir {
+        Some(topdir) => path.strip_prefix(topdir).unwrap_or(&path).to_path_buf(),
+        None => path.clone(),
+    };
+    write!(
+        info_file,
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        encode_path(&stored_path),
+        deletion_date()
+    )?;
+
+    let destination = files_dir.join(&trash_name);
+    if let Err(e) = move_item(&path, &destination) {
+        let _ = fs::remove_file(&info_path);
+        return Err(e);
+    }
+    Ok(destination)
+}
+
+//items on another filesystem than home go to that volume's .Trash/$UID or .Trash-$UID, if it can be used
+fn trash_for(path: &Path) -> (PathBuf, Option<PathBuf>) {
+    let home = home_trash();
+    let home_device = home
+        .ancestors()
+        .find_map(|dir| fs::metadata(dir).ok())
+        .map(|metadata| metadata.dev());
+    let path_device = fs::symlink_metadata(path).ok().map(|metadata| metadata.dev());
+    if home_device == path_device {
+        return (home, None);
+    }
+    let Some(topdir) = crate::mounts::mount_of(path).map(|mount| mount.mount_point) else {
+        return (home, None);
+    };
+    let uid = unsafe { libc::getuid() };
+    //a shared .Trash must be a real directory with the sticky bit set
+    let shared = topdir.join(".Trash");
+    if let Ok(metadata) = fs::symlink_metadata(&shared) {
+        if metadata.is_dir() && metadata.permissions().mode() & 0o1000 != 0 {
+            let trash = shared.join(uid.to_string());
+            if fs::create_dir_all(&trash).is_ok() {
+                return (trash, Some(topdir));
+            }
+        }
+    }
+    let trash = topdir.join(format!(".Trash-{}", uid));
+    if fs::create_dir_all(&trash).is_ok() {
+        let _ = fs::set_permissions(&trash, fs::Permissions::from_mode(0o700));
+        return (trash, Some(topdir));
+    }
+    (home, None)
+}
+
+pub fn delete_permanently(path: &Path) -> Result<(), std::io::Error> {
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+//rename only works within one filesystem, otherwise copy the item over and remove the original
+fn move_item(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            copy_recursive(from, to)?;
+            delete_permanently(from)
+        }
+        result => result,
+    }
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    let metadata = from.symlink_metadata()?;
+    if metadata.file_type().is_symlink() {
+        std::os::unix::fs::symlink(fs::read_link(from)?, to)?;
+    } else if metadata.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::set_permissions(to, metadata.permissions())?;
+    } else {
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
+//Path= in a .trashinfo is percent-encoded like a URL path
+fn encode_path(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+    let mut encoded = String::new();
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+//undoes the percent-encoding of Path= entries, which file:// URLs use too
+pub fn decode_path(value: &str) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Some(byte) = std::str::from_utf8(&bytes[i + 1..i + 3]).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    PathBuf::from(std::ffi::OsString::from_vec(decoded))
+}
+
+fn parse_deletion_date(date: &str) -> Option<SystemTime> {
+    let (day, time) = date.split_once('T')?;
+    let day: Vec<i32> = day.split('-').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let time: Vec<i32> = time.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    if day.len() != 3 || time.len() != 3 {
+        return None;
+    }
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    tm.tm_year = day[0] - 1900;
+    tm.tm_mon = day[1] - 1;
+    tm.tm_mday = day[2];
+    tm.tm_hour = time[0];
+    tm.tm_min = time[1];
+    tm.tm_sec = time[2];
+    tm.tm_isdst = -1;
+    let seconds = unsafe { libc::mktime(&mut tm) };
+    if seconds < 0 {
+        return None;
+    }
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds as u64))
+}
+
+//DeletionDate= is local time as YYYY-MM-DDThh:mm:ss
+fn deletion_date() -> String {
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        libc::localtime_r(&now, &mut tm);
+    }
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
+
+pub struct TrashPanel {
+    pub items: Vec<TrashItem>,
+    pub days: u64,
+    //files/ paths of the items to purge once the user confirms
+    confirming: Vec<PathBuf>,
+}
+
+impl Default for TrashPanel {
+    fn default() -> Self {
+        Self { items: Vec::new(), days: 30, confirming: Vec::new() }
+    }
+}
+
+impl TrashPanel {
+    pub fn refresh(&mut self) {
+        self.items = list_items();
+        self.confirming.clear();
+    }
+
+    //every trashed item with restore and purge buttons, plus purging by age; purging asks first
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            if ui.button("Refresh").clicked() {
+                changed = true;
+            }
+            let total: f64 = self.items.iter().map(|item| item.size).sum();
+            ui.label(format!("{} items, {}", self.items.len(), crate::format_size(total)));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Purge everything deleted more than");
+            ui.add(egui::DragValue::new(&mut self.days).clamp_range(0..=3650));
+            ui.label("days ago");
+            if ui.button("Purge").clicked() {
+                self.confirming = self.items.iter().filter(|item| item.older_than(self.days)).map(|item| item.files_path()).collect();
+            }
+        });
+        if !self.confirming.is_empty() {
+            changed |= self.confirm(ui);
+        }
+        ui.separator();
+        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            egui::Grid::new("trash_items").striped(true).show(ui, |ui| {
+                ui.strong("Original path");
+                ui.strong("Deleted");
+                ui.strong("Size");
+                ui.end_row();
+                for item in self.items.iter() {
+                    ui.label(item.original_path.to_string_lossy());
+                    ui.label(&item.deletion_date);
+                    ui.label(crate::format_size(item.size));
+                    if ui.button("Restore").clicked() {
+                        if let Err(e) = restore(item) {
+                            eprintln!("Error restoring {:?}: {:?}", item.original_path, e);
+                        }
+                        changed = true;
+                    }
+                    if ui.button("Purge").clicked() {
+                        self.confirming = vec![item.files_path()];
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        if changed {
+            self.refresh();
+        }
+    }
+
+    //returns true when items were purged
+    fn confirm(&mut self, ui: &mut egui::Ui) -> bool {
+        let chosen: Vec<&TrashItem> = self.items.iter().filter(|item| self.confirming.contains(&item.files_path())).collect();
+        let size: f64 = chosen.iter().map(|item| item.size).sum();
+        let mut done = false;
+        let mut cancelled = false;
+        ui.group(|ui| {
+            match chosen.as_slice() {
+                [item] => ui.label(format!("Purge {:?} ({})?", item.original_path, crate::format_size(size))),
+                _ => ui.label(format!("Purge {} items ({})?", chosen.len(), crate::format_size(size))),
+            };
+            ui.colored_label(egui::Color32::RED, "This cannot be undone.");
+            ui.horizontal(|ui| {
+                done = ui.button("Purge").clicked();
+                cancelled = ui.button("Cancel").clicked();
+            });
+        });
+        if done {
+            for item in chosen {
+                if let Err(e) = purge(item) {
+                    eprintln!("Error purging {:?}: {:?}", item.files_path(), e);
+                }
+            }
+        }
+        if done || cancelled {
+            self.confirming.clear();
+        }
+        done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_escapes_everything_but_safe_characters() {
+        assert_eq!(encode_path(Path::new("/home/me/a b/ü%.txt")), "/home/me/a%20b/%C3%BC%25.txt");
+        assert_eq!(encode_path(Path::new("/x/y-z_1.~")), "/x/y-z_1.~");
+    }
+
+    #[test]
+    fn decode_reverses_encode() {
+        for path in ["/home/me/a b/ü%.txt", "/tmp/100%", "relative/dir#1"] {
+            assert_eq!(decode_path(&encode_path(Path::new(path))), PathBuf::from(path));
+        }
+    }
+
+    #[test]
+    fn decode_keeps_invalid_escapes() {
+        assert_eq!(decode_path("/a%zz/b"), PathBuf::from("/a%zz/b"));
+        assert_eq!(decode_path("/a%4"), PathBuf::from("/a%4"));
+        assert_eq!(decode_path("/a%41"), PathBuf::from("/aA"));
+    }
+
+    #[test]
+    fn deletion_dates_round_trip() {
+        let parsed = parse_deletion_date(&deletion_date()).unwrap();
+        let now = SystemTime::now();
+        assert!(now.duration_since(parsed).unwrap_or_default() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn deletion_dates_are_ordered() {
+        let first = parse_deletion_date("2024-01-02T03:04:05").unwrap();
+        let second = parse_deletion_date("2024-01-02T03:04:06").unwrap();
+        let next_day = parse_deletion_date("2024-01-03T03:04:05").unwrap();
+        assert_eq!(second.duration_since(first).unwrap(), Duration::from_secs(1));
+        assert_eq!(next_day.duration_since(first).unwrap(), Duration::from_secs(24 * 60 * 60));
+    }
+
+    #[test]
+    fn malformed_deletion_dates_are_rejected() {
+        for date in ["", "2024-01-02", "2024-01-02 03:04:05", "2024-01T03:04:05", "2024-01-02T03:04", "2024-aa-02T03:04:05"] {
+            assert!(parse_deletion_date(date).is_none(), "{}", date);
+        }
+    }
+}